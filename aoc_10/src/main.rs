@@ -2,6 +2,9 @@
 extern crate regex;
 extern crate ncurses;
 
+mod ocr;
+mod player;
+
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
@@ -9,7 +12,6 @@ use std::collections::HashSet;
 use std::ops::Add;
 use std::ops::AddAssign;
 use regex::Regex;
-use ncurses::*;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 struct Pair {
@@ -58,50 +60,121 @@ fn read_input() -> Vec<Entry> {
     }
 
     reader.lines()
-        .flat_map(|line|line.ok())
+        .map(|line|line.unwrap())
         .flat_map(|line| LINE_RE.captures(&line)
                  .map(|cap| (Pair::from_str(&cap[2]), Pair::from_str(&cap[4]))))
         .map(|(position, velocity)| Entry { position, velocity })
         .collect()
 }
 
-fn print_output(coordinates: &Vec<Entry>, i: usize) {
-    let row_limit = 20;
-    let col_limit = 100;
-    let min_row = coordinates.iter().map(|e|e.position.y).min().unwrap();
-    let max_row = coordinates.iter().map(|e|e.position.y).max().unwrap();
-    let min_col = coordinates.iter().map(|e|e.position.x).min().unwrap();
-    let max_col = coordinates.iter().map(|e|e.position.x).max().unwrap();
+fn position_at(entry: &Entry, time: i64) -> (i64, i64) {
+    (entry.position.x as i64 + entry.velocity.x as i64 * time,
+     entry.position.y as i64 + entry.velocity.y as i64 * time)
+}
 
-    if (max_col - min_col + 1 > col_limit || max_row - min_row + 1 > row_limit) {
-        return;
+// Top-left and bottom-right corners of the points' bounding box
+fn bounds(points: &[(i64, i64)]) -> ((i64, i64), (i64, i64)) {
+    let min_x = points.iter().map(|p|p.0).min().unwrap();
+    let max_x = points.iter().map(|p|p.0).max().unwrap();
+    let min_y = points.iter().map(|p|p.1).min().unwrap();
+    let max_y = points.iter().map(|p|p.1).max().unwrap();
+    ((min_x, min_y), (max_x, max_y))
+}
+
+// Width plus height of the bounding box at the given time. Each of the four
+// box edges moves with whichever point is furthest out, so width and height
+// are convex in time, and so is their sum.
+fn spread(entries: &[Entry], time: i64) -> i64 {
+    let points = entries.iter().map(|e| position_at(e, time)).collect::<Vec<(i64, i64)>>();
+    let ((min_x, min_y), (max_x, max_y)) = bounds(&points);
+    (max_x - min_x) + (max_y - min_y)
+}
+
+// Find the second at which the bounding box is smallest, which is when the
+// message shows. Since the spread is convex, it's the first second after
+// which it stops shrinking: double an upper bound until the spread grows,
+// then binary search for the turning point.
+fn converge(entries: &[Entry]) -> i64 {
+    let shrinking = |time: i64| spread(entries, time + 1) < spread(entries, time);
+
+    let mut high = 1;
+    while shrinking(high) {
+        high *= 2;
     }
 
-    println!("{}: Min/Max Row: {}/{} ; Min/Max Col: {}/{}", i, min_row, max_row, min_col, max_col);
-    println!("{}:{}", max_row - min_row + 1, max_col - min_col + 1);
+    let mut low = 0;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if shrinking(mid) { low = mid + 1; } else { high = mid; }
+    }
+    low
+}
 
-    let coordinates = coordinates
-        .iter()
-        .map(|e| (e.position.x, e.position.y))
-        .collect::<HashSet<(i32,i32)>>();
+fn render(points: &[(i64, i64)]) -> String {
+    let ((min_x, min_y), (max_x, max_y)) = bounds(points);
+    let points = points.iter().cloned().collect::<HashSet<(i64, i64)>>();
 
-    for y in min_row..max_row+1 {
-        for x in min_col..max_col+1 {
-            print!("{}", if coordinates.contains(&(x,y)) { '#' } else { '.' });
+    let mut output = String::new();
+    for y in min_y..max_y + 1 {
+        for x in min_x..max_x + 1 {
+            output.push(if points.contains(&(x, y)) { '#' } else { '.' });
         }
-        println!("|");
+        output.push('\n');
     }
-
-    println!("--------------------------\n");
+    output
 }
 
+// Usage: aoc_10 [play | --json]
 fn main() {
-    let mut input = read_input();
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let json = args.iter().any(|arg| arg == "--json");
+
+    let input = read_input();
+    if input.is_empty() {
+        eprintln!("No points in input");
+        return;
+    }
+
+    let time = converge(&input);
+    let points = input.iter().map(|e| position_at(e, time)).collect::<Vec<(i64, i64)>>();
+    if args.iter().any(|arg| arg == "play") {
+        player::run(&input, time);
+        return;
+    }
+
+    let frame = render(&points);
+    let message = ocr::read(&frame);
+
+    if json {
+        let rows = frame.lines().map(|row| format!("{:?}", row)).collect::<Vec<String>>();
+        let result = match message {
+            Ok(message) => format!("\"message\": {:?}", message),
+            Err(e) => format!("\"error\": {:?}", e.to_string()),
+        };
+        println!("{{\"seconds\": {}, {}, \"frame\": [{}]}}", time, result, rows.join(", "));
+        return;
+    }
+
+    match message {
+        Ok(message) => println!("Message after {} seconds: {}", time, message),
+        Err(e) => {
+            println!("Message after {} seconds:", time);
+            eprintln!("Couldn't read the message: {}", e);
+        }
+    }
+    print!("{}", frame);
+}
 
-    for i in 1..12000 {
-        input
-            .iter_mut()
-            .for_each(|ref mut e| e.position += e.velocity.clone());
-        print_output(&input, i);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer() {
+        let input = read_input();
+        let time = converge(&input);
+        let points = input.iter().map(|e| position_at(e, time)).collect::<Vec<(i64, i64)>>();
+        assert_eq!(time, 10159);
+        assert_eq!(ocr::read(&render(&points)).unwrap(), "LKPHZHHJ");
     }
 }
@@ -0,0 +1,141 @@
+use std::fmt;
+
+const HEIGHT: usize = 10;
+const WIDTH: usize = 6;
+
+// The letters of the large font messages are drawn in, as far as they're known
+const FONT: [(char, [&str; HEIGHT]); 15] = [
+    ('A', ["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', ["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', [".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', [".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', ["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', ["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', ["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', ["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', ["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', ["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', ["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', ["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', ["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+#[derive(Debug)]
+pub enum OcrError {
+    // The frame isn't exactly one line of letters tall
+    Height { rows: usize },
+    // The columns of the frame starting at `column` don't match any letter
+    Unknown { column: usize, glyph: Vec<String> },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::Height { rows } =>
+                write!(f, "frame is {} rows tall, letters are {}", rows, HEIGHT),
+            OcrError::Unknown { column, glyph } =>
+                write!(f, "unknown glyph at column {}:\n{}", column, glyph.join("\n")),
+        }
+    }
+}
+
+fn recognise(glyph: &[String]) -> Option<char> {
+    FONT.iter()
+        .find(|(_, rows)| glyph.len() == HEIGHT && rows.iter().zip(glyph.iter()).all(|(a, b)| a == b))
+        .map(|(letter, _)| *letter)
+}
+
+// Read a frame as rendered by `render` ('#' for a point, '.' otherwise).
+// Letters are separated by empty columns.
+pub fn read(frame: &str) -> Result<String, OcrError> {
+    let rows = frame.lines().map(|line| line.chars().collect::<Vec<char>>()).collect::<Vec<Vec<char>>>();
+    if rows.len() != HEIGHT {
+        return Err(OcrError::Height { rows: rows.len() });
+    }
+
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let lit = |x: usize| rows.iter().any(|row| row.get(x) == Some(&'#'));
+
+    let mut message = String::new();
+    let mut x = 0;
+    while x < width {
+        if !lit(x) {
+            x += 1;
+            continue;
+        }
+
+        let start = x;
+        while x < width && lit(x) {
+            x += 1;
+        }
+
+        let glyph = rows.iter()
+            .map(|row| (start..x).map(|i| *row.get(i).unwrap_or(&'.')).collect::<String>())
+            .collect::<Vec<String>>();
+        let letter = if x - start == WIDTH { recognise(&glyph) } else { None };
+        match letter {
+            Some(letter) => message.push(letter),
+            None => return Err(OcrError::Unknown { column: start, glyph }),
+        }
+    }
+
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Draw letters from the font side by side, two empty columns apart
+    fn frame(letters: &str) -> String {
+        let glyphs = letters.chars()
+            .map(|c| FONT.iter().find(|(letter, _)| *letter == c).unwrap().1)
+            .collect::<Vec<[&str; HEIGHT]>>();
+        (0..HEIGHT)
+            .map(|row| glyphs.iter().map(|glyph| glyph[row]).collect::<Vec<&str>>().join("..") + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn reads_every_letter() {
+        let letters = FONT.iter().map(|(letter, _)| *letter).collect::<String>();
+        assert_eq!(read(&frame(&letters)).unwrap(), letters);
+        assert_eq!(read(&frame("LKPHZHHJ")).unwrap(), "LKPHZHHJ");
+    }
+
+    #[test]
+    fn wrong_height() {
+        let frame = frame("AB");
+        let short = frame.lines().take(9).collect::<Vec<&str>>().join("\n");
+        match read(&short) {
+            Err(OcrError::Height { rows: 9 }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn narrow_glyph() {
+        // An "I" only three columns wide, after a known letter
+        let narrow = ["###", ".#.", ".#.", ".#.", ".#.", ".#.", ".#.", ".#.", ".#.", "###"];
+        let input = frame("H").lines().zip(narrow.iter())
+            .map(|(h, i)| format!("{}..{}\n", h, i))
+            .collect::<String>();
+        match read(&input) {
+            Err(OcrError::Unknown { column: 8, ref glyph }) => assert_eq!(glyph[0], "###"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_glyph() {
+        // Six columns wide, but not a letter of the font
+        let input = frame("E").replace("#####.", "####..");
+        match read(&input) {
+            Err(OcrError::Unknown { column: 0, ref glyph }) => assert_eq!(glyph[4], "####.."),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(read(&input).unwrap_err().to_string().starts_with("unknown glyph at column 0:\n######"));
+    }
+}
@@ -0,0 +1,127 @@
+use ncurses::*;
+
+use ::Entry;
+use ::bounds;
+use ::position_at;
+
+const HELP: &str = "left/right: 1s  down/up: 100s  pgdn/pgup: 1000s  +/-: zoom  0: fit  space: play  g: message  q: quit";
+// Zoom levels available in either direction
+const MAX_ZOOM: i32 = 16;
+// Most seconds skipped per auto-play frame
+const PLAY_STRIDE: i64 = 64;
+// Delay between auto-play frames, in milliseconds
+const PLAY_DELAY: i32 = 50;
+
+struct Player<'a> {
+    entries: &'a [Entry],
+    // When the bounding box is smallest
+    message_time: i64,
+    time: i64,
+    // Zoom relative to fitting the whole bounding box on the screen: every
+    // level in halves the area shown, every level out doubles it
+    zoom: i32,
+    playing: bool,
+}
+
+impl<'a> Player<'a> {
+    fn step(&mut self, seconds: i64) {
+        self.time = (self.time + seconds).max(0);
+    }
+
+    // Advance while the bounding box keeps shrinking. Its size is convex in
+    // time, so it shrinks exactly until the message time; head there, slowing
+    // down on the way, and stop on it.
+    fn play(&mut self) {
+        let remaining = self.message_time - self.time;
+        if remaining <= 0 {
+            self.playing = false;
+            return;
+        }
+        self.time += (remaining / 4).clamp(1, PLAY_STRIDE);
+    }
+
+    fn draw(&self) {
+        let mut rows = 0;
+        let mut cols = 0;
+        getmaxyx(stdscr(), &mut rows, &mut cols);
+        // Keep the last two rows for the status line and the help
+        let rows = (rows - 2).max(1) as i64;
+        let cols = (cols as i64).max(1);
+
+        let points = self.entries.iter().map(|e| position_at(e, self.time)).collect::<Vec<(i64, i64)>>();
+        let ((min_x, min_y), (max_x, max_y)) = bounds(&points);
+        let (width, height) = (max_x - min_x + 1, max_y - min_y + 1);
+
+        // Sky units per screen cell
+        let fit = ((width + cols - 1) / cols).max((height + rows - 1) / rows);
+        let scale = if self.zoom >= 0 {
+            (fit >> self.zoom).max(1)
+        } else {
+            fit << -self.zoom
+        };
+
+        let left = (min_x + max_x) / 2 - cols / 2 * scale;
+        let top = (min_y + max_y) / 2 - rows / 2 * scale;
+
+        erase();
+        for (x, y) in points {
+            let col = (x - left).div_euclid(scale);
+            let row = (y - top).div_euclid(scale);
+            if (0..cols).contains(&col) && (0..rows).contains(&row) {
+                mvaddch(row as i32, col as i32, '#' as chtype);
+            }
+        }
+
+        let status = format!("t={}  box {}x{}  1 cell = {} units{}",
+                             self.time, width, height, scale,
+                             if self.playing { "  [playing]" } else { "" });
+        mvaddstr(rows as i32, 0, &status);
+        mvaddstr(rows as i32 + 1, 0, HELP);
+        refresh();
+    }
+}
+
+// Show the sky in the terminal, starting at second 0, until 'q' is pressed
+pub fn run(entries: &[Entry], message_time: i64) {
+    let mut player = Player { entries, message_time, time: 0, zoom: 0, playing: false };
+
+    initscr();
+    cbreak();
+    noecho();
+    keypad(stdscr(), true);
+    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+
+    loop {
+        player.draw();
+
+        timeout(if player.playing { PLAY_DELAY } else { -1 });
+        let key = getch();
+        if key == ERR {
+            player.play();
+            continue;
+        }
+
+        // Any key other than space stops auto-play
+        let playing = player.playing;
+        player.playing = false;
+        match key {
+            KEY_RIGHT => player.step(1),
+            KEY_LEFT => player.step(-1),
+            KEY_UP => player.step(100),
+            KEY_DOWN => player.step(-100),
+            KEY_PPAGE => player.step(1000),
+            KEY_NPAGE => player.step(-1000),
+            _ => match ::std::char::from_u32(key as u32) {
+                Some('+') | Some('=') => player.zoom = (player.zoom + 1).min(MAX_ZOOM),
+                Some('-') | Some('_') => player.zoom = (player.zoom - 1).max(-MAX_ZOOM),
+                Some('0') => player.zoom = 0,
+                Some(' ') => player.playing = !playing,
+                Some('g') => player.time = player.message_time,
+                Some('q') => break,
+                _ => {}
+            },
+        }
+    }
+
+    endwin();
+}
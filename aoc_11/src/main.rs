@@ -1,102 +1,115 @@
-use std::iter;
+mod sat;
+mod sweep;
+mod parallel;
 
-fn get_coordinates(from:i32, to:i32) -> Vec<(i32,i32)> {
-    (from..to+1)
-        .flat_map(|x|iter::repeat(x).take((to - from) as usize + 1))
-        .zip((from..to+1).cycle())
-        .collect::<Vec<(i32,i32)>>()
-}
+use std::env;
+use std::process;
+use std::thread;
+use std::time::Instant;
 
-#[derive(Debug)]
-struct VecGrid {
-    width: i32,
-    height: i32,
+use sat::SummedAreaTable;
+use sweep::Square;
 
-    // First value is the coordinate ordinal to which it belongs
-    // Second value is the 'generation' in which it was set
-    grid: Vec<i32>,
+fn power_level(x: usize, y: usize, serial: i64) -> i64 {
+    let rack_id = x as i64 + 10;
+    (((rack_id * y as i64) + serial) * rack_id) / 100 % 10 - 5
 }
 
-impl VecGrid {
-    fn new(width: i32, height: i32) -> VecGrid {
-        VecGrid {
-            width,
-            height,
-            grid: vec![std::i32::MIN; (width * height) as usize] }
-    }
-
-    fn set(&mut self, x: i32, y: i32, val: i32) {
-        let index = self.calculate_index(x,y);
-        self.grid[index] = val;
-    }
+// Cells are numbered from 1, so the table's (0, 0) is the cell (1, 1)
+fn create_table(width: usize, height: usize, serial: i64) -> SummedAreaTable<i64> {
+    SummedAreaTable::from_fn(width, height, |x, y| power_level(x + 1, y + 1, serial)).unwrap()
+}
 
-    fn get(&self, x: i32, y: i32) -> Option<&i32> {
-        if !self.in_bounds(x,y) { None } else { self.grid.get(self.calculate_index(x,y)) }
+fn print_squares(title: &str, squares: &[Square]) {
+    println!("  {} ({}):", title, squares.len());
+    for square in squares {
+        println!("    {}", square);
     }
+}
 
-    fn in_bounds(&self, x:i32, y:i32) -> bool {
-        let x = x - 1;
-        let y = y - 1;
-        y >= 0 && y < self.height && x >= 0 && x < self.width
+// Time the size sweep with and without threads and pruning. The threads can
+// only speed things up as far as there are cores to run them on.
+fn bench(table: &SummedAreaTable<i64>, threads: usize, runs: usize) {
+    let cores = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    if threads > cores {
+        println!("  Note: {} thread(s) on {} core(s); the parallel speedup is bounded by the cores, \
+                  the pruning speedup isn't", threads, cores);
     }
-
-    fn calculate_index(&self, x: i32, y: i32) -> usize {
-        let x = x - 1;
-        let y = y - 1;
-        (self.width * y + x) as usize
+    let variants = [("sequential", 1, false), ("parallel", threads, false),
+                    ("pruned", 1, true), ("parallel, pruned", threads, true)];
+
+    let mut baseline = None;
+    for (name, threads, prune) in variants.iter() {
+        let start = Instant::now();
+        let mut result = None;
+        for _ in 0..runs {
+            result = Some(parallel::sweep(table, *threads, *prune));
+        }
+        let elapsed = start.elapsed().as_secs_f64() / runs as f64;
+        let result = result.unwrap();
+        let baseline = *baseline.get_or_insert(elapsed);
+
+        println!("  {:<18} {} thread(s): {:>8.2}ms, {:>5.2}x, {} sizes scanned, {} pruned, best {}",
+                 name, threads, elapsed * 1000.0, baseline / elapsed, result.scanned, result.pruned,
+                 result.best.map_or("none".to_string(), |square| square.to_string()));
     }
 }
 
-fn create_grid(from:i32, to:i32, serial: i32) -> VecGrid {
-    get_coordinates(from, to)
-        .iter()
-        .fold(VecGrid::new(to-from+1, to-from+1), |mut acc, (x,y)| {
-            let rack_id = x + 10;
-            acc.set(*x,*y, (((rack_id * y)+serial) * rack_id)/100%10 - 5);
-            acc
-        })
-}
-
-fn create_pre_comp(from:i32, to:i32, grid: &VecGrid) -> VecGrid {
-    get_coordinates(from, to)
-        .iter()
-        .fold(VecGrid::new(to-from+1, to-from+1), |mut acc, (x,y)| {
-            let current = acc.get(x-1,*y).unwrap_or(&0)
-                + acc.get(*x,y-1).unwrap_or(&0)
-                - acc.get(x-1,y-1).unwrap_or(&0)
-                + grid.get(*x,*y).unwrap();
-            acc.set(*x, *y, current);
-            acc
-        })
-}
-
-
-fn find_max(from: i32, to: i32, size: i32, pre_comp: &VecGrid) -> ((i32,i32), i32, i32) {
-    get_coordinates(from, to - size + 1)
-        .iter()
-        .fold(((-1,-1),std::i32::MIN,size), |max, (x,y)| {
-            let val =
-                 pre_comp.get(x+size-1,y+size-1).unwrap_or(&0)
-                 - pre_comp.get(x-1,y+size-1).unwrap_or(&0)
-                 - pre_comp.get(x+size-1,y-1).unwrap_or(&0)
-                 + pre_comp.get(x-1,y-1).unwrap_or(&0);
-
-            if val <= max.1 { max } else { ((*x,*y), val, size) }
-        })
-}
-
+// Usage: aoc_11 [bench [--runs N]] [--width N] [--height N] [--threads N] [--no-prune]
+//               [--top K] [--per-size] [--local] [SERIAL...]
 fn main() {
-    let serial = 7803;
-    let from :i32 = 1;
-    let to :i32 = 300;
-
-    let grid = create_grid(from, to, serial);
-    let pre_comp = create_pre_comp(from, to, &grid);
-
-    let res = (from..to+1)
-        //.inspect(|x| println!("Size: {}", x))
-        .map(|x| find_max(from, to, x, &pre_comp))
-        .max_by_key(|x|x.1);
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let options = ["--width", "--height", "--top", "--threads", "--runs"];
+    let option = |name: &str, default: usize| match args.iter().position(|x| x == name) {
+        None => default,
+        Some(i) => args.get(i + 1).and_then(|x| x.parse::<usize>().ok()).unwrap_or_else(|| {
+            eprintln!("Usage: aoc_11 [bench [--runs N]] [--width N] [--height N] [--threads N] [--no-prune]");
+            eprintln!("              [--top K] [--per-size] [--local] [SERIAL...]");
+            process::exit(1);
+        }),
+    };
+    let width = option("--width", 300);
+    let height = option("--height", 300);
+    let top = option("--top", 5);
+    let threads = option("--threads", thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+    let prune = !args.iter().any(|x| x == "--no-prune");
+    let per_size = args.iter().any(|x| x == "--per-size");
+    let local = args.iter().any(|x| x == "--local");
+
+    // Whatever isn't an option or an option's value is a serial number
+    let mut serials = args.iter().enumerate()
+        .filter(|(i, x)| *x != "bench" && !x.starts_with("--") && (*i == 0 || !options.contains(&args[i - 1].as_str())))
+        .map(|(_, x)| x.parse::<i64>().unwrap())
+        .collect::<Vec<i64>>();
+    if serials.is_empty() {
+        serials.push(7803);
+    }
 
-    println!("{:?}", res);
+    for serial in serials {
+        let table = create_table(width, height, serial);
+        println!("Serial {} ({}x{}):", serial, width, height);
+        if args.first().is_some_and(|x| x == "bench") {
+            bench(&table, threads, option("--runs", 5));
+            continue;
+        }
+
+        match sweep::best(&table, 3) {
+            Some(square) => println!("  Best 3x3: {}", square),
+            None => println!("  Best 3x3: grid too small"),
+        }
+        match parallel::sweep(&table, threads, prune).best {
+            Some(square) => println!("  Best of any size: {}", square),
+            None => println!("  Best of any size: grid is empty"),
+        }
+
+        if per_size {
+            print_squares("Best per size", &sweep::best_per_size(&table));
+        }
+        if top > 0 {
+            print_squares(&format!("Top {}", top), &sweep::top_k(&table, top));
+        }
+        if local {
+            print_squares("Local maxima", &sweep::local_maxima(&table));
+        }
+    }
 }
@@ -0,0 +1,78 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use sat::SummedAreaTable;
+use sweep;
+use sweep::Square;
+
+pub struct Sweep {
+    pub best: Option<Square>,
+    // Number of sizes scanned, and skipped because they couldn't win
+    pub scanned: usize,
+    pub pruned: usize,
+}
+
+struct Shared {
+    // Best power of every size scanned so far, by size
+    maxima: Vec<Option<i64>>,
+    best: Option<Square>,
+    scanned: usize,
+    pruned: usize,
+}
+
+// The most power any square of the given size could have, given the best
+// powers of the sizes scanned so far. A square of size s holds (s / t)^2
+// disjoint squares of size t, none better than the best one of that size,
+// plus the remaining cells, none better than the best single cell.
+fn bound(maxima: &[Option<i64>], size: usize) -> Option<i64> {
+    let cell = maxima[1]?;
+    (2..size).filter_map(|tile| {
+        let best = maxima[tile]?;
+        let tiles = (size / tile) * (size / tile);
+        let rest = size * size - tiles * tile * tile;
+        Some(tiles as i64 * best + rest as i64 * cell)
+    }).min()
+}
+
+// Find the best square of any size, scanning sizes from the smallest up on
+// `threads` threads. With `prune`, sizes whose bound is below the best square
+// found so far are skipped.
+pub fn sweep(table: &SummedAreaTable<i64>, threads: usize, prune: bool) -> Sweep {
+    let largest = table.width().min(table.height());
+    let shared = Mutex::new(Shared { maxima: vec![None; largest + 1], best: None, scanned: 0, pruned: 0 });
+    let next = AtomicUsize::new(1);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| loop {
+                let size = next.fetch_add(1, Ordering::Relaxed);
+                if size > largest {
+                    break;
+                }
+
+                if prune {
+                    let mut shared = shared.lock().unwrap();
+                    let best = shared.best.map(|square| square.power);
+                    if let (Some(bound), Some(best)) = (bound(&shared.maxima, size), best) {
+                        if bound < best {
+                            shared.pruned += 1;
+                            continue;
+                        }
+                    }
+                }
+
+                let square = sweep::best(table, size).unwrap();
+                let mut shared = shared.lock().unwrap();
+                shared.maxima[size] = Some(square.power);
+                shared.scanned += 1;
+                if shared.best.is_none_or(|best| square.rank() > best.rank()) {
+                    shared.best = Some(square);
+                }
+            });
+        }
+    });
+
+    let shared = shared.into_inner().unwrap();
+    Sweep { best: shared.best, scanned: shared.scanned, pruned: shared.pruned }
+}
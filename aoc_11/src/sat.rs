@@ -0,0 +1,164 @@
+use std::fmt;
+
+// Numbers a table can hold. Sums are checked, so a table never holds a
+// wrapped-around value.
+pub trait Value: Copy + Default {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_value {
+    ($($t:ty),*) => {$(
+        impl Value for $t {
+            fn checked_add(self, other: $t) -> Option<$t> { <$t>::checked_add(self, other) }
+            fn checked_sub(self, other: $t) -> Option<$t> { <$t>::checked_sub(self, other) }
+        }
+    )*}
+}
+
+impl_value!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    // The rectangle doesn't fit in the grid
+    OutOfBounds { x: usize, y: usize, width: usize, height: usize },
+    // The sum of the rectangle at (x, y) doesn't fit in the value type
+    Overflow { x: usize, y: usize, width: usize, height: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfBounds { x, y, width, height } =>
+                write!(f, "{}x{} rectangle at ({}, {}) is out of the grid", width, height, x, y),
+            Error::Overflow { x, y, width, height } =>
+                write!(f, "sum of the {}x{} rectangle at ({}, {}) overflows", width, height, x, y),
+        }
+    }
+}
+
+// Sums of every rectangle of a grid, each in O(1). Coordinates are 0-based,
+// with (0, 0) at the top-left of the grid.
+pub struct SummedAreaTable<T> {
+    width: usize,
+    height: usize,
+    // Row-major, with an extra leading row and column of zeros: the entry at
+    // (x, y) is the sum of the grid's cells left of x and above y
+    sums: Vec<T>,
+}
+
+impl<T: Value> SummedAreaTable<T> {
+    // Every entry is built as the entry above it plus a running sum along its
+    // row. Both are sums of real rectangles of the grid, so building fails
+    // only if one of the rectangles starting at (0, 0) really overflows.
+    pub fn from_fn<F>(width: usize, height: usize, mut value: F) -> Result<SummedAreaTable<T>, Error>
+        where F: FnMut(usize, usize) -> T
+    {
+        let stride = width + 1;
+        let mut sums = vec![T::default(); stride * (height + 1)];
+
+        for y in 0..height {
+            let mut row = T::default();
+            for x in 0..width {
+                row = row.checked_add(value(x, y))
+                    .ok_or(Error::Overflow { x: 0, y, width: x + 1, height: 1 })?;
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1].checked_add(row)
+                    .ok_or(Error::Overflow { x: 0, y: 0, width: x + 1, height: y + 1 })?;
+            }
+        }
+
+        Ok(SummedAreaTable { width, height, sums })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn at(&self, x: usize, y: usize) -> T {
+        self.sums[y * (self.width + 1) + x]
+    }
+
+    // Sum of the `width` x `height` rectangle whose top-left cell is (x, y)
+    pub fn sum(&self, x: usize, y: usize, width: usize, height: usize) -> Result<T, Error> {
+        let fits = x.checked_add(width).is_some_and(|right| right <= self.width)
+            && y.checked_add(height).is_some_and(|bottom| bottom <= self.height);
+        if !fits {
+            return Err(Error::OutOfBounds { x, y, width, height });
+        }
+
+        // The sums of the columns x..x+width down to the bottom of the
+        // rectangle, and down to its top. Both are real rectangle sums, as is
+        // their difference, so an error means one of them doesn't fit.
+        let overflow = || Error::Overflow { x, y, width, height };
+        let (right, bottom) = (x + width, y + height);
+        let down_to_bottom = self.at(right, bottom).checked_sub(self.at(x, bottom)).ok_or_else(overflow)?;
+        let down_to_top = self.at(right, y).checked_sub(self.at(x, y)).ok_or_else(overflow)?;
+        down_to_bottom.checked_sub(down_to_top).ok_or_else(overflow)
+    }
+
+    pub fn square(&self, x: usize, y: usize, size: usize) -> Result<T, Error> {
+        self.sum(x, y, size, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(x: usize, y: usize) -> i64 {
+        ((x * 7 + y * 13) % 11) as i64 - 5
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let (width, height) = (7, 5);
+        let table = SummedAreaTable::from_fn(width, height, grid).unwrap();
+        for y in 0..height + 1 {
+            for x in 0..width + 1 {
+                for h in 0..height + 1 - y {
+                    for w in 0..width + 1 - x {
+                        let expected = (y..y + h).flat_map(|j| (x..x + w).map(move |i| grid(i, j))).sum::<i64>();
+                        assert_eq!(table.sum(x, y, w, h), Ok(expected));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let table = SummedAreaTable::from_fn(4, 3, |_, _| 1).unwrap();
+        assert_eq!(table.sum(0, 0, 4, 3), Ok(12));
+        assert_eq!(table.sum(1, 0, 4, 3), Err(Error::OutOfBounds { x: 1, y: 0, width: 4, height: 3 }));
+        assert_eq!(table.sum(0, 1, 1, 3), Err(Error::OutOfBounds { x: 0, y: 1, width: 1, height: 3 }));
+        assert_eq!(table.square(usize::MAX, 0, 2), Err(Error::OutOfBounds { x: usize::MAX, y: 0, width: 2, height: 2 }));
+    }
+
+    #[test]
+    fn overflow_while_building() {
+        let table = SummedAreaTable::<i8>::from_fn(2, 2, |_, _| 64);
+        assert_eq!(table.err(), Some(Error::Overflow { x: 0, y: 0, width: 2, height: 1 }));
+
+        let table = SummedAreaTable::<i8>::from_fn(1, 3, |_, _| 50);
+        assert_eq!(table.err(), Some(Error::Overflow { x: 0, y: 0, width: 1, height: 3 }));
+    }
+
+    #[test]
+    fn overflow_in_a_sum() {
+        // Every rectangle from (0, 0) fits, but the last two cells don't
+        let values = [-100i8, 100, 100];
+        let table = SummedAreaTable::from_fn(3, 1, |x, _| values[x]).unwrap();
+        assert_eq!(table.sum(0, 0, 3, 1), Ok(100));
+        assert_eq!(table.sum(2, 0, 1, 1), Ok(100));
+        assert_eq!(table.sum(1, 0, 2, 1), Err(Error::Overflow { x: 1, y: 0, width: 2, height: 1 }));
+
+        let table = SummedAreaTable::from_fn(2, 1, |x, _| [i8::MIN, i8::MAX][x]).unwrap();
+        assert_eq!(table.sum(0, 0, 2, 1), Ok(-1));
+        assert_eq!(table.sum(0, 0, 1, 1), Ok(i8::MIN));
+        assert_eq!(table.sum(1, 0, 1, 1), Ok(i8::MAX));
+    }
+}
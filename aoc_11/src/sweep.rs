@@ -0,0 +1,98 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+use sat::SummedAreaTable;
+
+// A square of fuel cells. The position is its top-left cell, numbered from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Square {
+    pub x: usize,
+    pub y: usize,
+    pub size: usize,
+    pub power: i64,
+}
+
+impl Square {
+    // Ordering for the best squares: more power first, then smaller squares,
+    // then top to bottom and left to right
+    pub fn rank(&self) -> (i64, Reverse<(usize, usize, usize)>) {
+        (self.power, Reverse((self.size, self.y, self.x)))
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{} (power {})", self.x, self.y, self.size, self.power)
+    }
+}
+
+fn largest_size(table: &SummedAreaTable<i64>) -> usize {
+    table.width().min(table.height())
+}
+
+// Every square of the given size, left to right and then top to bottom
+fn squares(table: &SummedAreaTable<i64>, size: usize) -> impl Iterator<Item = Square> + '_ {
+    (0..table.height() + 1 - size).flat_map(move |y| {
+        (0..table.width() + 1 - size).map(move |x| {
+            Square { x: x + 1, y: y + 1, size, power: table.square(x, y, size).unwrap() }
+        })
+    })
+}
+
+pub fn best(table: &SummedAreaTable<i64>, size: usize) -> Option<Square> {
+    if size == 0 || size > largest_size(table) {
+        return None;
+    }
+    squares(table, size).max_by_key(|square| square.rank())
+}
+
+pub fn best_per_size(table: &SummedAreaTable<i64>) -> Vec<Square> {
+    (1..largest_size(table) + 1).filter_map(|size| best(table, size)).collect()
+}
+
+// The k best squares of any size, best first
+pub fn top_k(table: &SummedAreaTable<i64>, k: usize) -> Vec<Square> {
+    // Min-heap of the best squares seen so far, worst on top
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for size in 1..largest_size(table) + 1 {
+        for square in squares(table, size) {
+            heap.push(Reverse((square.rank(), square.x, square.y, square.size, square.power)));
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+    }
+
+    heap.into_sorted_vec().into_iter()
+        .map(|Reverse((_, x, y, size, power))| Square { x, y, size, power })
+        .collect()
+}
+
+// Squares with more power than any of their neighbours: the squares one cell
+// away in any direction, including diagonally, with the same size or one
+// larger or smaller
+pub fn local_maxima(table: &SummedAreaTable<i64>) -> Vec<Square> {
+    let largest = largest_size(table);
+    let power = |x: usize, y: usize, size: usize| {
+        if size == 0 || x == 0 || y == 0 { None } else { table.square(x - 1, y - 1, size).ok() }
+    };
+
+    let mut maxima = Vec::new();
+    for size in 1..largest + 1 {
+        for square in squares(table, size) {
+            let is_max = (size - 1..size + 2).all(|s| {
+                (square.y - 1..square.y + 2).all(|y| {
+                    (square.x - 1..square.x + 2).all(|x| {
+                        (x, y, s) == (square.x, square.y, size)
+                            || power(x, y, s).is_none_or(|other| other < square.power)
+                    })
+                })
+            });
+            if is_max {
+                maxima.push(square);
+            }
+        }
+    }
+    maxima
+}
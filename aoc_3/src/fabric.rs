@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use ::Claim;
+
+// Keeps, for every square inch of fabric, the ids of all claims covering it,
+// so that overlap questions can be answered after the layout was built.
+pub struct Fabric<'a> {
+    claims: BTreeMap<usize, &'a Claim>,
+    cells: HashMap<(usize, usize), Vec<usize>>,
+}
+
+impl<'a> Fabric<'a> {
+    pub fn new(claims: &'a [Claim]) -> Fabric<'a> {
+        let mut cells = HashMap::new();
+        for claim in claims {
+            for pos in claim.cells() {
+                cells.entry(pos).or_insert_with(Vec::new).push(claim.id);
+            }
+        }
+
+        Fabric {
+            claims: claims.iter().map(|c| (c.id, c)).collect(),
+            cells,
+        }
+    }
+
+    pub fn claim(&self, id: usize) -> Option<&'a Claim> {
+        self.claims.get(&id).cloned()
+    }
+
+    // Ids of all claims covering the given square inch
+    pub fn claims_at(&self, x: usize, y: usize) -> &[usize] {
+        self.cells.get(&(x, y)).map(|ids| ids.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn contested_cells(&self) -> usize {
+        self.cells.values().filter(|ids| ids.len() > 1).count()
+    }
+
+    pub fn uncontested_cells(&self) -> usize {
+        self.cells.values().filter(|ids| ids.len() == 1).count()
+    }
+
+    // Ids of all other claims sharing at least one square inch with claim `id`
+    pub fn overlapping(&self, id: usize) -> BTreeSet<usize> {
+        self.claim(id)
+            .into_iter()
+            .flat_map(|claim| claim.cells())
+            .flat_map(|(x, y)| self.claims_at(x, y).iter().cloned())
+            .filter(|&other| other != id)
+            .collect()
+    }
+
+    // Number of square inches of claim `id` that are also covered by another claim
+    pub fn contested_area(&self, id: usize) -> usize {
+        self.claim(id)
+            .map(|claim| claim.cells().filter(|&(x, y)| self.claims_at(x, y).len() > 1).count())
+            .unwrap_or(0)
+    }
+
+    pub fn contested_areas(&self) -> BTreeMap<usize, usize> {
+        self.claims.keys().map(|&id| (id, self.contested_area(id))).collect()
+    }
+
+    pub fn non_overlapping(&self) -> Vec<&'a Claim> {
+        self.claims
+            .values()
+            .filter(|claim| self.contested_area(claim.id) == 0)
+            .cloned()
+            .collect()
+    }
+
+    // Undirected conflict graph: every claim maps to the set of claims it
+    // overlaps with. Claims without conflicts map to an empty set.
+    pub fn conflict_graph(&self) -> BTreeMap<usize, BTreeSet<usize>> {
+        let mut graph: BTreeMap<usize, BTreeSet<usize>> =
+            self.claims.keys().map(|&id| (id, BTreeSet::new())).collect();
+
+        for ids in self.cells.values().filter(|ids| ids.len() > 1) {
+            for &a in ids {
+                for &b in ids.iter().filter(|&&b| b != a) {
+                    graph.get_mut(&a).unwrap().insert(b);
+                }
+            }
+        }
+
+        graph
+    }
+
+    // Render the conflict graph in Graphviz DOT format. Each edge is labeled
+    // with the number of square inches shared by the two claims.
+    pub fn to_dot(&self) -> String {
+        let mut shared: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        for ids in self.cells.values().filter(|ids| ids.len() > 1) {
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    *shared.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut output = String::new();
+        writeln!(output, "graph claims {{").unwrap();
+        for id in self.claims.keys() {
+            writeln!(output, "    {};", id).unwrap();
+        }
+        for ((a, b), area) in shared {
+            writeln!(output, "    {} -- {} [label=\"{}\"];", a, b, area).unwrap();
+        }
+        writeln!(output, "}}").unwrap();
+        output
+    }
+}
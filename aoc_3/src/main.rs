@@ -1,11 +1,14 @@
 #[macro_use] extern crate lazy_static;
 extern crate regex;
 
+mod fabric;
+
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
-use std::collections::HashMap;
+use std::env;
 use regex::Regex;
+use fabric::Fabric;
 
 #[derive(Debug)]
 struct Claim {
@@ -31,6 +34,11 @@ impl Claim {
             height: captures[5].parse::<usize>().unwrap(),
         }
     }
+
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (left, top, height) = (self.left, self.top, self.height);
+        (left..left + self.width).flat_map(move |i| (top..top + height).map(move |j| (i, j)))
+    }
 }
 
 fn read_input() -> Vec<Claim> {
@@ -41,28 +49,39 @@ fn read_input() -> Vec<Claim> {
 
 fn main() {
     let input = read_input();
-    let mut fabric = HashMap::new();
+    let fabric = Fabric::new(&input);
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let args = args.iter().map(|a| a.as_str()).collect::<Vec<&str>>();
 
-    for claim in input.iter() {
-        for i in (claim.left..).take(claim.width) {
-            for j in (claim.top..).take(claim.height) {
-                *fabric.entry((i,j)).or_insert(0) += 1;
+    match args.as_slice() {
+        ["overlaps", id] => {
+            let id = id.parse::<usize>().unwrap();
+            println!("claim {} overlaps {:?}", id, fabric.overlapping(id));
+            println!("contested area {}", fabric.contested_area(id));
+        }
+        ["at", x, y] => {
+            let (x, y) = (x.parse::<usize>().unwrap(), y.parse::<usize>().unwrap());
+            println!("claims at {},{}: {:?}", x, y, fabric.claims_at(x, y));
+        }
+        ["contested"] => {
+            for (id, area) in fabric.contested_areas() {
+                println!("{}: {}", id, area);
             }
         }
-    }
-
-    println!("fabric size: {}", fabric.keys().count());
-    println!("num with more than 1 {}", fabric.values().filter(|&&x| x > 1).count());
-    println!("num with 1 {}", fabric.values().filter(|&&x| x == 1).count());
-
-    'claims: for claim in input {
-        for i in (claim.left..).take(claim.width) {
-            for j in (claim.top..).take(claim.height) {
-                if *fabric.get(&(i,j)).unwrap() > 1 { continue 'claims; }
+        ["graph"] => {
+            for (id, others) in fabric.conflict_graph() {
+                println!("{}: {:?}", id, others);
             }
         }
+        ["dot"] => print!("{}", fabric.to_dot()),
+        _ => {
+            println!("fabric size: {}", fabric.size());
+            println!("num with more than 1 {}", fabric.contested_cells());
+            println!("num with 1 {}", fabric.uncontested_cells());
 
-        println!("Found claim {:?}", claim);
-        break;
+            for claim in fabric.non_overlapping() {
+                println!("Found claim {:?}", claim);
+            }
+        }
     }
 }
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use chrono::*;
+
+use ::InputRecord;
+use ::ShiftStats;
+
+#[derive(Debug)]
+pub enum Anomaly {
+    Unparseable{line: String, date: Option<NaiveDateTime>},
+    ShiftWithoutGuard{date: NaiveDateTime},
+    NoActiveShift{date: NaiveDateTime},
+    DoubleSleep{date: NaiveDateTime},
+    WakeWithoutSleep{date: NaiveDateTime},
+    UnfinishedSleep{date: NaiveDateTime},
+    CrossesMidnight{sleep: NaiveDateTime, wake: NaiveDateTime},
+    OutsideMidnightHour{sleep: NaiveDateTime, wake: NaiveDateTime},
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Anomaly::Unparseable{line, date: Some(date)} => write!(f, "[{}] unparseable record: {:?}", date, line),
+            Anomaly::Unparseable{line, date: None} => write!(f, "unparseable record: {:?}", line),
+            Anomaly::ShiftWithoutGuard{date} => write!(f, "[{}] shift starts without a guard id", date),
+            Anomaly::NoActiveShift{date} => write!(f, "[{}] record outside of any guard's shift", date),
+            Anomaly::DoubleSleep{date} => write!(f, "[{}] falls asleep while already asleep", date),
+            Anomaly::WakeWithoutSleep{date} => write!(f, "[{}] wakes up without falling asleep", date),
+            Anomaly::UnfinishedSleep{date} => write!(f, "[{}] falls asleep and never wakes up", date),
+            Anomaly::CrossesMidnight{sleep, wake} =>
+                write!(f, "[{}] sleep crosses midnight (wakes at {})", sleep, wake),
+            Anomaly::OutsideMidnightHour{sleep, wake} =>
+                write!(f, "[{}] sleep outside the midnight hour (wakes at {})", sleep, wake),
+        }
+    }
+}
+
+struct Validator {
+    shifts: Vec<ShiftStats>,
+    anomalies: Vec<Anomaly>,
+    current: Option<ShiftStats>,
+    asleep: Option<NaiveDateTime>,
+}
+
+impl Validator {
+    fn new() -> Validator {
+        Validator { shifts: Vec::new(), anomalies: Vec::new(), current: None, asleep: None }
+    }
+
+    fn end_shift(&mut self) {
+        if let Some(sleep) = self.asleep.take() {
+            self.anomalies.push(Anomaly::UnfinishedSleep{ date: sleep });
+        }
+        if let Some(shift) = self.current.take() {
+            self.shifts.push(shift);
+        }
+    }
+
+    fn process(&mut self, record: &InputRecord) {
+        match *record {
+            InputRecord::ShiftStart{guard_id, date} => {
+                self.end_shift();
+                self.current = Some(ShiftStats::new(guard_id, shift_date(&date)));
+            }
+            InputRecord::FallAsleep{date} => {
+                if self.current.is_none() {
+                    self.anomalies.push(Anomaly::NoActiveShift{ date });
+                } else if self.asleep.is_some() {
+                    self.anomalies.push(Anomaly::DoubleSleep{ date });
+                } else {
+                    self.asleep = Some(date);
+                }
+            }
+            InputRecord::WakeUp{date: wake} => {
+                if self.current.is_none() {
+                    self.anomalies.push(Anomaly::NoActiveShift{ date: wake });
+                    return;
+                }
+
+                let sleep = match self.asleep.take() {
+                    Some(sleep) => sleep,
+                    None => {
+                        self.anomalies.push(Anomaly::WakeWithoutSleep{ date: wake });
+                        return;
+                    }
+                };
+
+                if sleep.date() != wake.date() {
+                    self.anomalies.push(Anomaly::CrossesMidnight{ sleep, wake });
+                } else if sleep.hour() != 0 || !in_midnight_hour(&wake) {
+                    self.anomalies.push(Anomaly::OutsideMidnightHour{ sleep, wake });
+                } else {
+                    self.current.as_mut().unwrap().update(&sleep, &wake);
+                }
+            }
+        }
+    }
+}
+
+// Whether a wake up ends a sleep within the midnight hour, which includes
+// waking up right at 01:00
+fn in_midnight_hour(wake: &NaiveDateTime) -> bool {
+    wake.hour() == 0 || (wake.hour() == 1 && wake.minute() == 0)
+}
+
+// A guard starting before midnight is on duty for the next day's midnight hour
+fn shift_date(start: &NaiveDateTime) -> NaiveDate {
+    if start.hour() >= 12 { start.date().succ_opt().unwrap() } else { start.date() }
+}
+
+// Run the chronologically sorted log lines through a state machine, returning
+// the shifts that could be reconstructed along with every anomaly found on
+// the way. Records involved in an anomaly don't contribute to any shift.
+pub fn validate(lines: &[String]) -> (Vec<ShiftStats>, Vec<Anomaly>) {
+    let mut validator = Validator::new();
+
+    for line in lines {
+        match InputRecord::from_input(line) {
+            Some(record) => validator.process(&record),
+            None if line.contains("begins shift") && InputRecord::date(line).is_some() => {
+                // Whatever follows can't be attributed to the previous guard
+                validator.end_shift();
+                validator.anomalies.push(Anomaly::ShiftWithoutGuard{ date: InputRecord::date(line).unwrap() });
+            }
+            None => validator.anomalies.push(Anomaly::Unparseable{ line: line.clone(), date: InputRecord::date(line) }),
+        }
+    }
+
+    validator.end_shift();
+    (validator.shifts, validator.anomalies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(lines: &[&str]) -> (Vec<ShiftStats>, Vec<Anomaly>) {
+        validate(&lines.iter().map(|line| line.to_string()).collect::<Vec<String>>())
+    }
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn double_sleep() {
+        let (shifts, anomalies) = run(&[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:10] falls asleep",
+            "[1518-11-01 00:20] wakes up",
+        ]);
+        assert!(matches!(anomalies[..], [Anomaly::DoubleSleep{ date }] if date == at("1518-11-01 00:10")));
+        assert_eq!(shifts[0].minutes.len(), 15);
+    }
+
+    #[test]
+    fn wake_without_sleep() {
+        let (shifts, anomalies) = run(&[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:20] wakes up",
+        ]);
+        assert!(matches!(anomalies[..], [Anomaly::WakeWithoutSleep{ date }] if date == at("1518-11-01 00:20")));
+        assert!(shifts[0].minutes.is_empty());
+    }
+
+    #[test]
+    fn record_before_first_shift() {
+        let (shifts, anomalies) = run(&[
+            "[1518-10-31 23:50] falls asleep",
+            "[1518-11-01 00:00] Guard #10 begins shift",
+        ]);
+        assert!(matches!(anomalies[..], [Anomaly::NoActiveShift{ date }] if date == at("1518-10-31 23:50")));
+        assert_eq!(shifts.len(), 1);
+    }
+
+    #[test]
+    fn crossing_midnight() {
+        let (shifts, anomalies) = run(&[
+            "[1518-10-31 23:30] Guard #10 begins shift",
+            "[1518-10-31 23:45] falls asleep",
+            "[1518-11-01 00:15] wakes up",
+        ]);
+        assert!(matches!(anomalies[..], [Anomaly::CrossesMidnight{ sleep, wake }]
+                         if sleep == at("1518-10-31 23:45") && wake == at("1518-11-01 00:15")));
+        assert!(shifts[0].minutes.is_empty());
+    }
+
+    #[test]
+    fn wake_at_one() {
+        let (shifts, anomalies) = run(&[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:50] falls asleep",
+            "[1518-11-01 01:00] wakes up",
+            "[1518-11-02 00:00] Guard #10 begins shift",
+            "[1518-11-02 00:50] falls asleep",
+            "[1518-11-02 01:01] wakes up",
+        ]);
+        assert_eq!(shifts[0].minutes.len(), 10);
+        assert!(shifts[0].minutes.contains(&59));
+        assert!(matches!(anomalies[..], [Anomaly::OutsideMidnightHour{ .. }]));
+    }
+
+    #[test]
+    fn unparseable() {
+        let (_, anomalies) = run(&["[1518-11-01 00:00] snores", "gibberish"]);
+        assert!(matches!(anomalies[..], [
+            Anomaly::Unparseable{ date: Some(date), .. },
+            Anomaly::Unparseable{ date: None, .. },
+        ] if date == at("1518-11-01 00:00")));
+        assert_eq!(anomalies[0].to_string(), "[1518-11-01 00:00:00] unparseable record: \"[1518-11-01 00:00] snores\"");
+    }
+}
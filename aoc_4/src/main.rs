@@ -2,13 +2,19 @@
 extern crate regex;
 extern crate chrono;
 
+mod ingest;
+mod report;
+mod strategy;
+
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::env;
 use regex::Regex;
 use chrono::*;
+use strategy::Strategy;
 
 #[derive(Debug)]
 enum InputRecord {
@@ -18,33 +24,32 @@ enum InputRecord {
 }
 
 impl InputRecord {
-    fn from_input(line: &str) -> Option<InputRecord> {
+    fn date(line: &str) -> Option<NaiveDateTime> {
         lazy_static! {
            static ref DATE_RE: Regex = Regex::new(r"^\[(\d+)-(\d+)-(\d+) (\d+):(\d+)\]").unwrap();
-           static ref SHIFT_START: Regex = Regex::new(r"Guard #(\d+) begins shift$").unwrap();
-           static ref FALL_ASLEEP: Regex = Regex::new(r"falls asleep").unwrap();
-           static ref WAKE_UP: Regex = Regex::new(r"wakes up").unwrap();
         }
 
-        let captures = DATE_RE.captures(line);
-        let date = captures.map(
-            |captures| NaiveDate::from_ymd(
-                captures[1].parse::<i32>().unwrap(),
-                captures[2].parse::<u32>().unwrap(),
-                captures[3].parse::<u32>().unwrap())
-            .and_hms(
-                captures[4].parse::<u32>().unwrap(),
-                captures[5].parse::<u32>().unwrap(),
-                0));
-
-        if date.is_none() {
-            return None;
+        let captures = DATE_RE.captures(line)?;
+        NaiveDate::from_ymd_opt(
+                captures[1].parse::<i32>().ok()?,
+                captures[2].parse::<u32>().ok()?,
+                captures[3].parse::<u32>().ok()?)?
+            .and_hms_opt(
+                captures[4].parse::<u32>().ok()?,
+                captures[5].parse::<u32>().ok()?,
+                0)
+    }
+
+    fn from_input(line: &str) -> Option<InputRecord> {
+        lazy_static! {
+           static ref SHIFT_START: Regex = Regex::new(r"Guard #(\d+) begins shift$").unwrap();
+           static ref FALL_ASLEEP: Regex = Regex::new(r"falls asleep$").unwrap();
+           static ref WAKE_UP: Regex = Regex::new(r"wakes up$").unwrap();
         }
 
-        let date = date.unwrap();
-        let captures = SHIFT_START.captures(line);
-        if captures.is_some() {
-            return Some(InputRecord::ShiftStart{ guard_id: captures.unwrap()[1].parse::<u32>().unwrap(), date });
+        let date = InputRecord::date(line)?;
+        if let Some(captures) = SHIFT_START.captures(line) {
+            return Some(InputRecord::ShiftStart{ guard_id: captures[1].parse::<u32>().ok()?, date });
         }
 
         if FALL_ASLEEP.is_match(line) {
@@ -58,27 +63,34 @@ impl InputRecord {
     }
 }
 
-fn read_input() -> Vec<InputRecord> {
+fn read_input() -> Vec<String> {
     let input_file = File::open("src/input.txt").unwrap();
     let reader = BufReader::new(input_file);
-    let mut lines = reader.lines().map(|x|x.unwrap()).collect::<Vec<String>>();
+    let mut lines = reader.lines()
+        .map(|x|x.unwrap())
+        .filter(|x| !x.trim().is_empty())
+        .collect::<Vec<String>>();
     lines.as_mut_slice().sort();
-    lines.iter().filter_map(|x| InputRecord::from_input(&x)).collect()
+    lines
 }
 
 #[derive(Debug)]
 struct ShiftStats {
     guard_id: u32,
+    date: NaiveDate,
     minutes: HashSet<u32>,
 }
 
 impl ShiftStats {
-    fn new(id: u32) -> ShiftStats {
-        ShiftStats { guard_id: id, minutes: HashSet::new() }
+    fn new(id: u32, date: NaiveDate) -> ShiftStats {
+        ShiftStats { guard_id: id, date, minutes: HashSet::new() }
     }
 
+    // Mark the minutes of the midnight hour spent asleep. A wake up at 01:00
+    // counts as minute 60.
     fn update(&mut self, sleep: &NaiveDateTime, wake: &NaiveDateTime) {
-        for min in sleep.time().minute()..wake.time().minute() {
+        let end = if wake.hour() == 1 { 60 } else { wake.minute() };
+        for min in sleep.minute()..end {
             self.minutes.insert(min);
         }
     }
@@ -104,54 +116,51 @@ impl GuardStats {
     fn total(&self) -> u32 {
         self.minute_counts.values().sum()
     }
+
+    fn count(&self, minute: u32) -> u32 {
+        *self.minute_counts.get(&minute).unwrap_or(&0)
+    }
+
+    // The minute most often slept on along with its frequency. Ties go to the
+    // earliest minute.
+    fn sleepiest_minute(&self) -> Option<(u32, u32)> {
+        self.minute_counts.iter()
+            .map(|(&min, &freq)| (min, freq))
+            .max_by_key(|&(min, freq)| (freq, ::std::cmp::Reverse(min)))
+    }
 }
 
 fn main() {
-    let records = read_input();
+    let (shifts, anomalies) = ingest::validate(&read_input());
+
+    for anomaly in anomalies.iter() {
+        eprintln!("{}", anomaly);
+    }
 
     let mut stats : HashMap<u32, GuardStats> = HashMap::new();
+    for shift in shifts.iter() {
+        stats.entry(shift.guard_id).or_insert_with(GuardStats::new).update(shift);
+    }
 
-    let mut current_shift = ShiftStats::new(0);
-
-    let mut i = records.iter();
-    loop {
-        let record = i.next();
-        if record.is_none(){ break; }
-
-        let record = record.unwrap();
-
-        match record {
-            InputRecord::ShiftStart{guard_id, date} => {
-                stats.entry(current_shift.guard_id).or_insert(GuardStats::new()).update(&current_shift);
-                current_shift = ShiftStats::new(*guard_id);
-            }
-            InputRecord::FallAsleep{date:sleep} => {
-                let wake = i.next().unwrap();
-                if let InputRecord::WakeUp{date:wake} = wake {
-                    current_shift.update(sleep, wake);
-                }
-            }
-            _ => panic!("Unexpected type")
+    if env::args().nth(1).as_deref() == Some("report") {
+        match env::args().nth(2).as_deref() {
+            Some("csv") => print!("{}", report::guards_csv(&stats)),
+            Some("timeline-csv") => print!("{}", report::timeline_csv(&shifts)),
+            _ => print!("{}", report::text(&stats, &shifts)),
         }
+        return;
     }
 
-    stats.entry(current_shift.guard_id).or_insert(GuardStats::new()).update(&current_shift);
-
-    //for (k,v) in stats {
-        //println!("ID: {:?} Total: {:?}", k, v.total());
-    //}
-
-    let guard = stats.iter()
-        .max_by_key(|(k,v)| v.total())
-        .map(|(k,v)| (k, v.minute_counts.iter().max_by_key(|(min,freq)|*freq)));
-    println!("{:?} {}", guard,  guard.unwrap().0 * guard.unwrap().1.unwrap().0);
-
-    let guard =
-        stats
-            .iter()
-            .max_by_key(|(k,v)| v.minute_counts.values().max());
+    if let (Some(first), Some(last)) = (shifts.first(), shifts.last()) {
+        println!("{} shifts from {} to {}, {} anomalies", shifts.len(), first.date, last.date, anomalies.len());
+    }
 
-    let id = guard.unwrap().0;
-    let min = guard.unwrap().1.minute_counts.iter().max_by_key(|(k,v)|*v).unwrap().0;
-    println!("ID {} min {} result {}", id, min, id * min);
+    let strategies: Vec<&dyn Strategy> = vec![&strategy::MostAsleep, &strategy::MostFrequentMinute];
+    for strategy in strategies {
+        match strategy::evaluate(strategy, &stats) {
+            Some(choice) => println!("{}: ID {} min {} result {}",
+                strategy.name(), choice.guard_id, choice.minute, choice.answer()),
+            None => println!("{}: no guard found", strategy.name()),
+        }
+    }
 }
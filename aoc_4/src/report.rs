@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use ::GuardStats;
+use ::ShiftStats;
+
+const MINUTES: u32 = 60;
+
+fn by_id(stats: &HashMap<u32, GuardStats>) -> BTreeMap<u32, &GuardStats> {
+    stats.iter().map(|(&id, guard)| (id, guard)).collect()
+}
+
+// Per-minute counts rendered one character per minute, in base 36 so that
+// they line up with the minute header
+fn histogram(guard: &GuardStats) -> String {
+    (0..MINUTES)
+        .map(|min| match guard.count(min) {
+            0 => '.',
+            n => ::std::char::from_digit(n.min(35), 36).unwrap(),
+        })
+        .collect()
+}
+
+fn timeline(shift: &ShiftStats) -> String {
+    (0..MINUTES).map(|min| if shift.minutes.contains(&min) { '#' } else { '.' }).collect()
+}
+
+fn minute_header(output: &mut String, indent: usize) {
+    let tens = (0..MINUTES).map(|min| (b'0' + (min / 10) as u8) as char).collect::<String>();
+    let ones = (0..MINUTES).map(|min| (b'0' + (min % 10) as u8) as char).collect::<String>();
+    writeln!(output, "{:indent$}{}", "", tens, indent = indent).unwrap();
+    writeln!(output, "{:indent$}{}", "", ones, indent = indent).unwrap();
+}
+
+pub fn text(stats: &HashMap<u32, GuardStats>, shifts: &[ShiftStats]) -> String {
+    let mut output = String::new();
+
+    writeln!(output, "Guards").unwrap();
+    writeln!(output, "{:>6} {:>6} {:>9} {:>5}  Minute", "ID", "Total", "Sleepiest", "Freq").unwrap();
+    minute_header(&mut output, 31);
+    for (id, guard) in by_id(stats) {
+        let (minute, freq) = guard.sleepiest_minute()
+            .map(|(min, freq)| (min.to_string(), freq.to_string()))
+            .unwrap_or_else(|| ("-".to_string(), "-".to_string()));
+        writeln!(output, "{:>6} {:>6} {:>9} {:>5}  {}", id, guard.total(), minute, freq, histogram(guard)).unwrap();
+    }
+
+    writeln!(output).unwrap();
+    writeln!(output, "Shifts").unwrap();
+    writeln!(output, "{:<10} {:>6}  Minute", "Date", "ID").unwrap();
+    minute_header(&mut output, 19);
+    for shift in shifts {
+        writeln!(output, "{} {:>6}  {}", shift.date, shift.guard_id, timeline(shift)).unwrap();
+    }
+
+    output
+}
+
+// One row per guard: id, total minutes asleep, sleepiest minute and its
+// frequency, followed by the number of shifts asleep on each minute
+pub fn guards_csv(stats: &HashMap<u32, GuardStats>) -> String {
+    let mut output = String::new();
+
+    write!(output, "guard_id,total,sleepiest_minute,frequency").unwrap();
+    for min in 0..MINUTES {
+        write!(output, ",m{:02}", min).unwrap();
+    }
+    writeln!(output).unwrap();
+
+    for (id, guard) in by_id(stats) {
+        let (minute, freq) = guard.sleepiest_minute()
+            .map(|(min, freq)| (min.to_string(), freq.to_string()))
+            .unwrap_or_default();
+        write!(output, "{},{},{},{}", id, guard.total(), minute, freq).unwrap();
+        for min in 0..MINUTES {
+            write!(output, ",{}", guard.count(min)).unwrap();
+        }
+        writeln!(output).unwrap();
+    }
+
+    output
+}
+
+// One row per shift: date, guard id and whether the guard was asleep (1) or
+// awake (0) on each minute of the midnight hour
+pub fn timeline_csv(shifts: &[ShiftStats]) -> String {
+    let mut output = String::new();
+
+    write!(output, "date,guard_id").unwrap();
+    for min in 0..MINUTES {
+        write!(output, ",m{:02}", min).unwrap();
+    }
+    writeln!(output).unwrap();
+
+    for shift in shifts {
+        write!(output, "{},{}", shift.date, shift.guard_id).unwrap();
+        for min in 0..MINUTES {
+            write!(output, ",{}", if shift.minutes.contains(&min) { 1 } else { 0 }).unwrap();
+        }
+        writeln!(output).unwrap();
+    }
+
+    output
+}
@@ -0,0 +1,146 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use ::GuardStats;
+
+// A way of picking a guard and a minute out of the aggregated statistics.
+// The guard with the highest rank wins, ties going to the lowest guard id.
+// Guards without a rank are never picked.
+pub trait Strategy {
+    fn name(&self) -> &str;
+
+    fn rank(&self, guard: &GuardStats) -> Option<u32>;
+
+    fn minute(&self, guard: &GuardStats) -> Option<u32> {
+        guard.sleepiest_minute().map(|(min, _)| min)
+    }
+}
+
+// Strategy 1: the guard with the most minutes asleep, on their sleepiest minute
+pub struct MostAsleep;
+
+impl Strategy for MostAsleep {
+    fn name(&self) -> &str { "Strategy 1" }
+
+    fn rank(&self, guard: &GuardStats) -> Option<u32> {
+        Some(guard.total())
+    }
+}
+
+// Strategy 2: the guard most frequently asleep on the same minute
+pub struct MostFrequentMinute;
+
+impl Strategy for MostFrequentMinute {
+    fn name(&self) -> &str { "Strategy 2" }
+
+    fn rank(&self, guard: &GuardStats) -> Option<u32> {
+        guard.sleepiest_minute().map(|(_, freq)| freq)
+    }
+}
+
+// A strategy ranking guards with an arbitrary function, for trying out
+// rankings in the tests. The minute is still the guard's sleepiest one.
+// Strategies meant to be kept implement `Strategy` themselves.
+#[cfg(test)]
+pub struct RankBy<F> {
+    name: String,
+    rank: F,
+}
+
+#[cfg(test)]
+impl<F: Fn(&GuardStats) -> Option<u32>> RankBy<F> {
+    pub fn new(name: &str, rank: F) -> RankBy<F> {
+        RankBy { name: name.to_string(), rank }
+    }
+}
+
+#[cfg(test)]
+impl<F: Fn(&GuardStats) -> Option<u32>> Strategy for RankBy<F> {
+    fn name(&self) -> &str { &self.name }
+
+    fn rank(&self, guard: &GuardStats) -> Option<u32> {
+        (self.rank)(guard)
+    }
+}
+
+#[derive(Debug)]
+pub struct Choice {
+    pub guard_id: u32,
+    pub minute: u32,
+}
+
+impl Choice {
+    pub fn answer(&self) -> u32 {
+        self.guard_id * self.minute
+    }
+}
+
+pub fn evaluate(strategy: &dyn Strategy, stats: &HashMap<u32, GuardStats>) -> Option<Choice> {
+    stats.iter()
+        .filter_map(|(&id, guard)| strategy.rank(guard).map(|rank| (rank, id, guard)))
+        .max_by_key(|&(rank, id, _)| (rank, Reverse(id)))
+        .and_then(|(_, id, guard)| strategy.minute(guard).map(|minute| Choice { guard_id: id, minute }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::ingest;
+
+    const EXAMPLE: [&str; 17] = [
+        "[1518-11-01 00:00] Guard #10 begins shift",
+        "[1518-11-01 00:05] falls asleep",
+        "[1518-11-01 00:25] wakes up",
+        "[1518-11-01 00:30] falls asleep",
+        "[1518-11-01 00:55] wakes up",
+        "[1518-11-01 23:58] Guard #99 begins shift",
+        "[1518-11-02 00:40] falls asleep",
+        "[1518-11-02 00:50] wakes up",
+        "[1518-11-03 00:05] Guard #10 begins shift",
+        "[1518-11-03 00:24] falls asleep",
+        "[1518-11-03 00:29] wakes up",
+        "[1518-11-04 00:02] Guard #99 begins shift",
+        "[1518-11-04 00:36] falls asleep",
+        "[1518-11-04 00:46] wakes up",
+        "[1518-11-05 00:03] Guard #99 begins shift",
+        "[1518-11-05 00:45] falls asleep",
+        "[1518-11-05 00:55] wakes up",
+    ];
+
+    fn stats() -> HashMap<u32, GuardStats> {
+        let lines = EXAMPLE.iter().map(|line| line.to_string()).collect::<Vec<String>>();
+        let (shifts, anomalies) = ingest::validate(&lines);
+        assert!(anomalies.is_empty());
+
+        let mut stats = HashMap::new();
+        for shift in shifts.iter() {
+            stats.entry(shift.guard_id).or_insert_with(GuardStats::new).update(shift);
+        }
+        stats
+    }
+
+    fn answer(strategy: &dyn Strategy) -> Option<u32> {
+        evaluate(strategy, &stats()).map(|choice| choice.answer())
+    }
+
+    #[test]
+    fn puzzle_strategies() {
+        assert_eq!(answer(&MostAsleep), Some(240));
+        assert_eq!(answer(&MostFrequentMinute), Some(4455));
+    }
+
+    #[test]
+    fn custom_strategies() {
+        // Guard 10 slept on 49 different minutes, guard 99 on 19
+        let widest = RankBy::new("Most distinct minutes", |guard: &GuardStats| Some(guard.minute_counts.len() as u32));
+        assert_eq!(widest.name(), "Most distinct minutes");
+        assert_eq!(answer(&widest), Some(240));
+
+        // Ties go to the lowest id
+        assert_eq!(answer(&RankBy::new("Anyone", |_: &GuardStats| Some(1))), Some(240));
+        assert_eq!(answer(&RankBy::new("Often on 45", |guard: &GuardStats| {
+            if guard.count(45) > 1 { Some(1) } else { None }
+        })), Some(99 * 45));
+        assert_eq!(answer(&RankBy::new("Nobody", |_: &GuardStats| None)), None);
+    }
+}
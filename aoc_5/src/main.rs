@@ -1,45 +1,71 @@
+mod reaction;
+mod removal;
+mod stream;
+
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
-use std::collections::HashSet;
+use std::env;
+use std::io;
+use std::process;
+use reaction::Rules;
 
 fn read_input() -> String {
     let input_file = File::open("src/input.txt").unwrap();
     let reader = BufReader::new(input_file);
-    reader.lines().map(|x|x.unwrap()).filter(|x|!x.is_empty()).next().unwrap_or("".to_string())
+    reader.lines().map(|x|x.unwrap()).find(|x|!x.is_empty()).unwrap_or("".to_string())
 }
 
-fn react<'a, I>(input: I) -> String
-    where I: Iterator<Item = char>
-{
-    let mut stack: Vec<char> = Vec::new();
-    for c in input {
-        if stack.last().map_or(false, |&x| x.to_ascii_uppercase() == c.to_ascii_uppercase() && x != c) {
-            stack.pop();
-            continue;
-        }
-
-        stack.push(c);
-    }
-
-    stack.into_iter().collect()
+fn read_rules(path: &str) -> Rules {
+    let mut rules = String::new();
+    File::open(path).unwrap().read_to_string(&mut rules).unwrap();
+    Rules::from_input(&rules).unwrap()
 }
 
+// Usage: aoc_5 [--rules FILE] [--stream]
+//
+// With `--stream` the polymer is read from stdin and the reduced polymer
+// written to stdout, with the reaction statistics going to stderr.
 fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let rules = args.iter()
+        .position(|a| a == "--rules")
+        .map(|i| match args.get(i + 1) {
+            Some(path) => read_rules(path),
+            None => {
+                eprintln!("Usage: aoc_5 [--rules FILE] [--stream]");
+                process::exit(1);
+            }
+        })
+        .unwrap_or_else(Rules::opposite_case);
+
+    if args.iter().any(|a| a == "--stream") {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        let summary = stream::reduce(&rules, stdin.lock(), stdout.lock()).unwrap();
+        eprintln!("Output size: {}", summary.len);
+        for ((a, b), count) in summary.counts.iter() {
+            eprintln!("{}/{}: {}", a, b, count);
+        }
+        return;
+    }
+
     let input = read_input();
-    println!("Output size: {}", react(input.chars()).len());
-
-    let unique_units =
-        input.chars()
-            .map(|x| x.to_ascii_uppercase())
-            .fold(HashSet::new(), |mut acc, c| { acc.insert(c); acc });
-    println!("Unique units: {:?}", unique_units);
-
-    let min_item =
-        unique_units.iter()
-            .map(|&u| (u, react(input.chars().filter(|&c| c.to_ascii_uppercase() != u)).len()))
-            .inspect(|(u,s)| println!("{},{}", u, s))
-            .min_by_key(|(_,s)| *s)
-            .unwrap();
-    println!("Min item {:?}", min_item);
+
+    let reaction = rules.react(&input);
+    println!("Output size: {}", reaction.len);
+    if reaction.polymer.len() <= 80 {
+        println!("Output: {}", reaction.polymer);
+    }
+    for ((a, b), count) in reaction.counts.iter() {
+        println!("{}/{}: {}", a, b, count);
+    }
+
+    let ranked = removal::rank_removals(&rules, &input);
+    for candidate in ranked.iter() {
+        println!("{},{}", candidate.units.join("/"), candidate.len);
+    }
+    if let Some(best) = ranked.first() {
+        println!("Min item {:?}", best);
+    }
 }
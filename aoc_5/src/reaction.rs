@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+// The set of unit pairs that annihilate each other when adjacent. Units are
+// arbitrary non-empty strings; anything in the polymer that isn't a known unit
+// is treated as an inert single-character unit.
+#[derive(Debug)]
+pub struct Rules {
+    ids: HashMap<String, usize>,
+    units: Vec<String>,
+    pairs: HashMap<(usize, usize), usize>,
+    max_len: usize,
+}
+
+#[derive(Debug)]
+pub struct Reaction {
+    pub polymer: String,
+    pub len: usize,
+    // Number of times each pair of units reacted, for pairs that did
+    pub counts: BTreeMap<(String, String), usize>,
+}
+
+impl Rules {
+    pub fn new() -> Rules {
+        Rules { ids: HashMap::new(), units: Vec::new(), pairs: HashMap::new(), max_len: 0 }
+    }
+
+    // The puzzle's rule: the same letter in opposite cases annihilates
+    pub fn opposite_case() -> Rules {
+        (b'a'..=b'z')
+            .map(|c| c as char)
+            .fold(Rules::new(), |rules, c| rules.with_pair(&c.to_string(), &c.to_ascii_uppercase().to_string()))
+    }
+
+    // Parse one whitespace-separated pair of units per line
+    pub fn from_input(input: &str) -> Result<Rules, String> {
+        input.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .try_fold(Rules::new(), |rules, line| {
+                match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+                    [a, b] => Ok(rules.with_pair(a, b)),
+                    _ => Err(format!("expected two units, got {:?}", line)),
+                }
+            })
+    }
+
+    pub fn with_pair(mut self, a: &str, b: &str) -> Rules {
+        assert!(!a.is_empty() && !b.is_empty(), "units can't be empty");
+        let (a, b) = (self.unit_id(a), self.unit_id(b));
+        let pair = self.pairs.len();
+        self.pairs.entry((a.min(b), a.max(b))).or_insert(pair);
+        self
+    }
+
+    fn unit_id(&mut self, unit: &str) -> usize {
+        if let Some(&id) = self.ids.get(unit) {
+            return id;
+        }
+
+        let id = self.units.len();
+        self.ids.insert(unit.to_string(), id);
+        self.units.push(unit.to_string());
+        self.max_len = self.max_len.max(unit.len());
+        id
+    }
+
+    pub fn reacts(&self, a: usize, b: usize) -> Option<usize> {
+        self.pairs.get(&(a.min(b), a.max(b))).cloned()
+    }
+
+    pub fn max_unit_len(&self) -> usize {
+        self.max_len
+    }
+
+    // The unit at the start of `rest`, preferring the longest known one, and
+    // its length in bytes
+    pub fn next_unit(&self, rest: &str) -> Option<(Option<usize>, usize)> {
+        let c = rest.chars().next()?;
+        let known = (1..=self.max_len.min(rest.len()))
+            .rev()
+            .filter(|&len| rest.is_char_boundary(len))
+            .find_map(|len| self.ids.get(&rest[..len]).map(|&id| (Some(id), len)));
+        Some(known.unwrap_or((None, c.len_utf8())))
+    }
+
+    // Split the polymer into units, preferring the longest known unit at every
+    // position
+    pub fn tokenize<'a>(&self, polymer: &'a str) -> Vec<(Option<usize>, &'a str)> {
+        let mut tokens = Vec::new();
+        let mut rest = polymer;
+        while let Some((id, len)) = self.next_unit(rest) {
+            tokens.push((id, &rest[..len]));
+            rest = &rest[len..];
+        }
+        tokens
+    }
+
+    pub fn pair_name(&self, pair: usize) -> (String, String) {
+        self.pairs.iter()
+            .find(|&(_, &p)| p == pair)
+            .map(|(&(a, b), _)| (self.units[a].clone(), self.units[b].clone()))
+            .unwrap()
+    }
+
+    pub fn unit(&self, id: usize) -> &str {
+        &self.units[id]
+    }
+
+    // Group the known units into types: units that react with each other,
+    // directly or through a chain of pairs, are of the same type. Returns the
+    // type index of every unit id.
+    pub fn unit_types(&self) -> Vec<usize> {
+        let mut types = (0..self.units.len()).collect::<Vec<usize>>();
+        fn root(types: &mut [usize], id: usize) -> usize {
+            let mut id = id;
+            while types[id] != id {
+                types[id] = types[types[id]];
+                id = types[id];
+            }
+            id
+        }
+
+        for &(a, b) in self.pairs.keys() {
+            let (a, b) = (root(&mut types, a), root(&mut types, b));
+            types[a.max(b)] = a.min(b);
+        }
+        (0..types.len()).map(|id| root(&mut types, id)).collect()
+    }
+
+    // Whether every unit is a single character that reacts with at most one
+    // partner, as with the puzzle's rule
+    pub fn is_simple(&self) -> bool {
+        let mut partners = vec![0; self.units.len()];
+        for &(a, b) in self.pairs.keys() {
+            partners[a] += 1;
+            if a != b { partners[b] += 1; }
+        }
+        self.max_len <= 1 && partners.iter().all(|&n| n <= 1)
+    }
+
+    pub fn react(&self, polymer: &str) -> Reaction {
+        self.react_units(self.tokenize(polymer))
+    }
+
+    // The single-pass stack reduction, over an already tokenized polymer
+    pub fn react_units<'a, I>(&self, units: I) -> Reaction
+        where I: IntoIterator<Item = (Option<usize>, &'a str)>
+    {
+        let mut stack: Vec<(Option<usize>, &str)> = Vec::new();
+        let mut counts = vec![0; self.pairs.len()];
+
+        for (id, unit) in units {
+            let reaction = match (stack.last().and_then(|&(top, _)| top), id) {
+                (Some(top), Some(id)) => self.reacts(top, id),
+                _ => None,
+            };
+
+            match reaction {
+                Some(pair) => {
+                    stack.pop();
+                    counts[pair] += 1;
+                }
+                None => stack.push((id, unit)),
+            }
+        }
+
+        Reaction {
+            len: stack.len(),
+            polymer: stack.into_iter().map(|(_, unit)| unit).collect(),
+            counts: self.named_counts(counts),
+        }
+    }
+
+    // Name the pairs in per-pair reaction counts, dropping those that never
+    // reacted
+    pub fn named_counts(&self, counts: Vec<usize>) -> BTreeMap<(String, String), usize> {
+        counts.into_iter()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .map(|(pair, count)| (self.pair_name(pair), count))
+            .collect()
+    }
+
+    pub fn pair_count(&self) -> usize {
+        self.pairs.len()
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::thread;
+
+use reaction::Rules;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum UnitType<'a> {
+    Known(usize),
+    Inert(&'a str),
+}
+
+#[derive(Debug)]
+pub struct Candidate {
+    // All the units removed together, e.g. `a` and `A`
+    pub units: Vec<String>,
+    pub len: usize,
+}
+
+fn unit_type<'a>(types: &[usize], &(id, unit): &(Option<usize>, &'a str)) -> UnitType<'a> {
+    match id {
+        Some(id) => UnitType::Known(types[id]),
+        None => UnitType::Inert(unit),
+    }
+}
+
+// Try removing every unit type present in the polymer and report the length
+// each removal reduces to, shortest first.
+//
+// With simple rules, where every unit is a single character reacting with
+// just its one partner, reductions can happen in any order and give the same
+// result. A pair that reacts in the raw polymer then still reacts once a whole
+// unit type is gone, so removing the type from the already reduced polymer
+// gives the same result, and makes every candidate much cheaper to evaluate.
+// That doesn't hold once a unit reacts with several others: with pairs `a b`,
+// `b c`, `c d` and `x X`, `axbcXd` reduces to `ad`, which stays as it is
+// without `x`/`X`, while `abcd` reduces to nothing. Other rules start from the
+// raw polymer.
+pub fn rank_removals(rules: &Rules, polymer: &str) -> Vec<Candidate> {
+    let types = rules.unit_types();
+
+    let mut present: BTreeMap<UnitType, Vec<String>> = BTreeMap::new();
+    for token in rules.tokenize(polymer) {
+        let units = present.entry(unit_type(&types, &token)).or_default();
+        if !units.iter().any(|u| u == token.1) {
+            units.push(token.1.to_string());
+        }
+    }
+    for (id, &t) in types.iter().enumerate() {
+        if let Some(units) = present.get_mut(&UnitType::Known(t)) {
+            if !units.iter().any(|u| u == rules.unit(id)) {
+                units.push(rules.unit(id).to_string());
+            }
+        }
+    }
+
+    let reduced = if rules.is_simple() { rules.react(polymer).polymer } else { polymer.to_string() };
+    let tokens = rules.tokenize(&reduced);
+    let candidates = present.into_iter().collect::<Vec<(UnitType, Vec<String>)>>();
+    let workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = candidates.len().div_ceil(workers).max(1);
+
+    let mut ranked = thread::scope(|scope| {
+        let handles = candidates.chunks(chunk_size)
+            .map(|chunk| {
+                let tokens = &tokens;
+                let types = &types;
+                scope.spawn(move || {
+                    chunk.iter()
+                        .map(|(removed, units)| {
+                            let remaining = tokens.iter().filter(|token| unit_type(types, token) != *removed).cloned();
+                            let mut units = units.clone();
+                            units.sort();
+                            Candidate { units, len: rules.react_units(remaining).len }
+                        })
+                        .collect::<Vec<Candidate>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect::<Vec<Candidate>>()
+    });
+
+    ranked.sort_by(|a, b| a.len.cmp(&b.len).then_with(|| a.units.cmp(&b.units)));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lengths(rules: &Rules, polymer: &str) -> Vec<(String, usize)> {
+        rank_removals(rules, polymer).into_iter().map(|c| (c.units.join("/"), c.len)).collect()
+    }
+
+    #[test]
+    fn opposite_case() {
+        let ranked = lengths(&Rules::opposite_case(), "dabAcCaCBAcCcaDA");
+        assert_eq!(ranked[0], ("C/c".to_string(), 4));
+        assert_eq!(ranked.len(), 4);
+    }
+
+    #[test]
+    fn chained_pairs() {
+        let rules = Rules::new().with_pair("a", "b").with_pair("b", "c").with_pair("c", "d").with_pair("x", "X");
+        assert!(!rules.is_simple());
+        let ranked = lengths(&rules, "axbcXd");
+        assert!(ranked.contains(&("X/x".to_string(), 0)));
+    }
+}
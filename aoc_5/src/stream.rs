@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::io::prelude::*;
+use std::str;
+
+use reaction::Rules;
+
+const CHUNK_SIZE: usize = 1 << 16;
+
+// Units on the stack are kept as a `u32` each: known units by their id, and
+// inert characters with the top bit set. The stack is all that's kept in
+// memory, so it's at most four times the size of the input.
+const INERT: u32 = 1 << 31;
+
+fn known(id: usize) -> u32 {
+    assert!(id < INERT as usize, "too many units");
+    id as u32
+}
+
+#[derive(Debug)]
+pub struct Summary {
+    pub len: usize,
+    pub counts: BTreeMap<(String, String), usize>,
+}
+
+// Reduce a polymer of any size, reading it from `input` in chunks. Only the
+// stack of units that haven't reacted yet is kept in memory; once the input is
+// exhausted it's written out to `output`. Whitespace in the input is dropped
+// before looking for units, so that polymers, and units within them, may be
+// split across lines.
+pub fn reduce<R: Read, W: Write>(rules: &Rules, mut input: R, mut output: W) -> io::Result<Summary> {
+    let mut stack: Vec<u32> = Vec::new();
+    let mut counts = vec![0; rules.pair_count()];
+    // Bytes read but not decoded yet, when a chunk ends in the middle of a
+    // character
+    let mut undecoded: Vec<u8> = Vec::new();
+    // Decoded text, without whitespace, that hasn't been split into units yet
+    let mut pending = String::new();
+    let mut chunk = vec![0; CHUNK_SIZE];
+    let mut done = false;
+
+    while !done {
+        let read = input.read(&mut chunk)?;
+        done = read == 0;
+        undecoded.extend_from_slice(&chunk[..read]);
+
+        let valid = match str::from_utf8(&undecoded) {
+            Ok(valid) => valid.len(),
+            Err(e) if e.error_len().is_none() && !done => e.valid_up_to(),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        pending.extend(str::from_utf8(&undecoded[..valid]).unwrap().chars().filter(|c| !c.is_whitespace()));
+        undecoded.drain(..valid);
+
+        // The longest unit starting near the end may continue into the next
+        // chunk
+        let lookahead = if done { 0 } else { rules.max_unit_len() };
+
+        let mut consumed = 0;
+        while pending.len() - consumed > lookahead {
+            let (id, len) = rules.next_unit(&pending[consumed..]).unwrap();
+            let unit = match id {
+                Some(id) => known(id),
+                None => INERT | pending[consumed..].chars().next().unwrap() as u32,
+            };
+            consumed += len;
+
+            let reaction = match stack.last() {
+                Some(&top) if top < INERT && unit < INERT => rules.reacts(top as usize, unit as usize),
+                _ => None,
+            };
+
+            match reaction {
+                Some(pair) => {
+                    stack.pop();
+                    counts[pair] += 1;
+                }
+                None => stack.push(unit),
+            }
+        }
+
+        pending.drain(..consumed);
+    }
+
+    let mut writer = io::BufWriter::new(&mut output);
+    for &unit in stack.iter() {
+        if unit < INERT {
+            writer.write_all(rules.unit(unit as usize).as_bytes())?;
+        } else {
+            write!(writer, "{}", char::from_u32(unit & !INERT).unwrap())?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(Summary { len: stack.len(), counts: rules.named_counts(counts) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hands out at most `step` bytes per read, to split units and characters
+    // across chunks
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.step.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    fn check(rules: &Rules, polymer: &str) {
+        let expected = rules.react(&polymer.split_whitespace().collect::<String>());
+        for step in 1..5 {
+            let mut output = Vec::new();
+            let summary = reduce(rules, Trickle { data: polymer.as_bytes(), step }, &mut output).unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), expected.polymer, "step {}", step);
+            assert_eq!(summary.len, expected.len);
+            assert_eq!(summary.counts, expected.counts);
+        }
+    }
+
+    #[test]
+    fn opposite_case() {
+        check(&Rules::opposite_case(), "dabAcCaCBAcCcaDA");
+        check(&Rules::opposite_case(), "dabAc\nCaCBA\ncCcaDA\n");
+    }
+
+    #[test]
+    fn multi_byte_and_multi_char_units() {
+        let rules = Rules::new().with_pair("é", "É").with_pair("ab", "ba").with_pair("abc", "x");
+        check(&rules, "aébaÉabcxab bax");
+        check(&rules, "ééÉÉabcabxba");
+    }
+
+    #[test]
+    fn units_split_across_lines() {
+        let rules = Rules::new().with_pair("ab", "x");
+        check(&rules, "a\nbx");
+        check(&rules, "xa\r\nb a \tb\nx");
+        check(&Rules::new().with_pair("é", "ab"), "a\n\nbé");
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let data = [b'a', 0xff, b'A'];
+        let result = reduce(&Rules::opposite_case(), Trickle { data: &data, step: 1 }, Vec::new());
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,14 +1,19 @@
+mod metric;
+mod voronoi;
+
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
 use std::collections::HashSet;
 use std::collections::HashMap;
+use std::env;
+use metric::{Method, Metric, Neighbourhood};
 
 fn read_input() -> Vec<(isize, isize)> {
     let input_file = File::open("src/input.txt").unwrap();
     let reader = BufReader::new(input_file);
     reader.lines()
-        .flat_map(|line|line.ok())
+        .map(|line|line.unwrap())
         .map(|line|line
              .split(',')
              .map(|x|x.trim().parse::<isize>().unwrap())
@@ -31,8 +36,10 @@ impl ProcessingEntry {
 }
 
 
+// Covers the bounding box of the input points, which may lie anywhere
 #[derive(Debug)]
 struct Grid {
+    min: (isize, isize),
     width: isize,
     height: isize,
 
@@ -42,8 +49,19 @@ struct Grid {
 }
 
 impl Grid {
-    fn new(width: isize, height: isize) -> Grid {
-        Grid { width: width as isize, height: height as isize, grid: vec![(-1, -1); (width * height) as usize] }
+    fn new(min: (isize, isize), max: (isize, isize)) -> Grid {
+        let width = max.0 - min.0 + 1;
+        let height = max.1 - min.1 + 1;
+        Grid { min, width, height, grid: vec![(-1, -1); (width * height) as usize] }
+    }
+
+    // The bounding box of the input, grown by `margin` on every side
+    fn bounding_box(input: &[(isize, isize)], margin: isize) -> Grid {
+        let min_x = input.iter().map(|(x,_)|*x).min().unwrap();
+        let min_y = input.iter().map(|(_,y)|*y).min().unwrap();
+        let max_x = input.iter().map(|(x,_)|*x).max().unwrap();
+        let max_y = input.iter().map(|(_,y)|*y).max().unwrap();
+        Grid::new((min_x - margin, min_y - margin), (max_x + margin, max_y + margin))
     }
 
     fn set(&mut self, entry: &ProcessingEntry) {
@@ -69,13 +87,21 @@ impl Grid {
     }
 
     fn in_bounds(&self, coordinates: (isize,isize)) -> bool {
-        let y = coordinates.1;
-        let x = coordinates.0;
+        let y = coordinates.1 - self.min.1;
+        let x = coordinates.0 - self.min.0;
         y >= 0 && y < self.height && x >= 0 && x < self.width
     }
 
     fn calculate_index(&self, coordinates: (isize, isize)) -> usize {
-        (self.width * coordinates.1 + coordinates.0) as usize
+        (self.width * (coordinates.1 - self.min.1) + coordinates.0 - self.min.0) as usize
+    }
+
+    fn border(&self) -> Vec<(isize, isize)> {
+        let (min_x, min_y) = self.min;
+        let (max_x, max_y) = (min_x + self.width - 1, min_y + self.height - 1);
+        (min_x..=max_x).flat_map(|x| vec![(x, min_y), (x, max_y)])
+            .chain((min_y..=max_y).flat_map(|y| vec![(min_x, y), (max_x, y)]))
+            .collect()
     }
 
     //fn print(&self) {
@@ -88,18 +114,11 @@ impl Grid {
     //}
 }
 
-fn part_1(input: &Vec<(isize, isize)>) {
-    let max_x = input.iter().map(|(x,_)|x).max().unwrap();
-    let max_y = input.iter().map(|(_,y)|y).max().unwrap();
-
-    println!("Max cooridnates: x {} y {}", max_x, max_y);
-
-    let mut grid = Grid::new(max_x + 2, max_y + 1);
-
-    let mut infinite = HashSet::new();
-    infinite.insert(-1);
-
-    let mut stacks = vec![vec![], vec![]];
+// Assign every location within the grid to the closest input point, by
+// growing all the areas one step at a time. Locations that are equally far
+// from several points belong to none (ordinal -1).
+fn flood(input: &[(isize, isize)], neighbourhood: Neighbourhood, mut grid: Grid) -> Grid {
+    let mut stacks = [vec![], vec![]];
     let mut current_gen = 0;
     for (i, coordinates) in input.iter().enumerate() {
         stacks[0].push(ProcessingEntry::new(i as i32, 0, *coordinates));
@@ -114,12 +133,6 @@ fn part_1(input: &Vec<(isize, isize)>) {
         stacks[next_gen_index].clear();
 
         for entry in stacks[current_gen_index].iter() {
-            if !grid.in_bounds(entry.coordinates) {
-                infinite.insert(entry.ordinal);
-                continue;
-            }
-
-            //println!("Setting {:?} on grid", entry);
             grid.set(entry);
         }
 
@@ -130,64 +143,363 @@ fn part_1(input: &Vec<(isize, isize)>) {
                 .filter(|e| grid.get(e.coordinates).1 == current_gen as i32) {
             let ordinal = grid.get(entry.coordinates).0;
 
-            next_gen.entry((entry.coordinates.0 + 1, entry.coordinates.1))
-                .and_modify(|e| if e.0 != entry.ordinal {e.0 = -1})
-                .or_insert((ordinal, entry.gen + 1));
-            next_gen.entry((entry.coordinates.0 - 1, entry.coordinates.1))
-                .and_modify(|e| if e.0 != entry.ordinal {e.0 = -1})
-                .or_insert((ordinal, entry.gen + 1));
-            next_gen.entry((entry.coordinates.0, entry.coordinates.1 + 1))
-                .and_modify(|e| if e.0 != entry.ordinal {e.0 = -1})
-                .or_insert((ordinal, entry.gen + 1));
-            next_gen.entry((entry.coordinates.0, entry.coordinates.1 - 1))
-                .and_modify(|e| if e.0 != entry.ordinal {e.0 = -1})
-                .or_insert((ordinal, entry.gen + 1));
+            for (dx, dy) in neighbourhood.offsets() {
+                next_gen.entry((entry.coordinates.0 + dx, entry.coordinates.1 + dy))
+                    .and_modify(|e| if e.0 != entry.ordinal {e.0 = -1})
+                    .or_insert((ordinal, entry.gen + 1));
+            }
         }
         stacks[next_gen_index] = next_gen.iter()
+            .filter(|(k,_)| grid.in_bounds(**k))
             .map(|(k,v)| ProcessingEntry::new(v.0, v.1, *k))
             .collect();
 
-
         current_gen += 1;
-        //grid.print();
-        //println!("-------------------------");
     }
 
-    let freq = grid.grid.iter()
-        .map(|(ordinal, _)| ordinal)
-        .filter(|val| !infinite.contains(*val))
-        .fold(HashMap::new(), |mut acc, val| { *acc.entry(val).or_insert(0) += 1; acc });
+    grid
+}
+
+// Assign every location within the grid to the closest input point by
+// measuring the distance to each of them
+fn nearest(input: &[(isize, isize)], metric: Metric, mut grid: Grid) -> Grid {
+    for y in grid.min.1..grid.min.1 + grid.height {
+        for x in grid.min.0..grid.min.0 + grid.width {
+            let mut distances = input.iter()
+                .enumerate()
+                .map(|(i, point)| (metric.key(*point, (x, y)), i as i32));
+            let first = distances.next().unwrap();
+            let (_, ordinal, tied) = distances.fold((first.0, first.1, false), |(best, ordinal, tied), (d, i)| {
+                if d < best { (d, i, false) } else if d == best { (best, ordinal, true) } else { (best, ordinal, tied) }
+            });
+
+            let index = grid.calculate_index((x, y));
+            grid.grid[index] = (if tied { -1 } else { ordinal }, 0);
+        }
+    }
+
+    grid
+}
+
+// Chebyshev distance is Manhattan distance on the coordinates turned by 45
+// degrees, u = x + y and v = x - y (halved), so the Manhattan reasoning below
+// applies in u and v
+fn rotate((x, y): (isize, isize)) -> (isize, isize) {
+    (x + y, x - y)
+}
+
+// The range of u and v covered by the points
+fn rotated_box(input: &[(isize, isize)]) -> ((isize, isize), (isize, isize)) {
+    let rotated = input.iter().map(|p| rotate(*p)).collect::<Vec<(isize, isize)>>();
+    let min_u = rotated.iter().map(|(u,_)|*u).min().unwrap();
+    let max_u = rotated.iter().map(|(u,_)|*u).max().unwrap();
+    let min_v = rotated.iter().map(|(_,v)|*v).min().unwrap();
+    let max_v = rotated.iter().map(|(_,v)|*v).max().unwrap();
+    ((min_u, min_v), (max_u, max_v))
+}
+
+// A grid holding every finite region, and enough of the infinite ones to tell
+// which they are
+fn regions_grid(input: &[(isize, isize)], metric: Metric) -> Grid {
+    match metric {
+        Metric::Manhattan => Grid::bounding_box(input, 0),
+        Metric::Chebyshev => {
+            // The rotated box and two more steps of u and v around it, so that
+            // a location of either parity lies past every side
+            let ((min_u, min_v), (max_u, max_v)) = rotated_box(input);
+            let (min_u, min_v, max_u, max_v) = (min_u - 2, min_v - 2, max_u + 2, max_v + 2);
+            Grid::new(((min_u + min_v).div_euclid(2), (min_u - max_v).div_euclid(2)),
+                      ((max_u + max_v + 1).div_euclid(2), (max_u - min_v + 1).div_euclid(2)))
+        }
+        Metric::Euclidean => {
+            let infinite = voronoi::infinite(input);
+            let grid = Grid::bounding_box(input, 0);
+            let mut min = grid.min;
+            let mut max = (grid.min.0 + grid.width - 1, grid.min.1 + grid.height - 1);
+            for i in (0..input.len()).filter(|&i| !infinite[i]) {
+                let (cell_min, cell_max) = voronoi::cell_bounds(input, i);
+                min = (min.0.min(cell_min.0), min.1.min(cell_min.1));
+                max = (max.0.max(cell_max.0), max.1.max(cell_max.1));
+            }
+            Grid::new(min, max)
+        }
+    }
+}
+
+fn regions(input: &[(isize, isize)], method: Method) -> Grid {
+    let grid = regions_grid(input, method.metric());
+    match method {
+        Method::Flood(neighbourhood) => flood(input, neighbourhood, grid),
+        Method::Nearest(metric) => nearest(input, metric, grid),
+    }
+}
+
+// Ordinals of the infinite regions, along with -1 for tied locations
+fn infinite_regions(input: &[(isize, isize)], metric: Metric, grid: &Grid) -> HashSet<i32> {
+    let mut infinite = HashSet::new();
+    infinite.insert(-1);
+
+    match metric {
+        // Past the bounding box, every step away takes a location further
+        // from all the points by the same amount, so whichever point owns a
+        // border location owns everything straight outward from it, and any
+        // region reaching past the box reaches its border
+        Metric::Manhattan => {
+            for coordinates in grid.border() {
+                infinite.insert(grid.get(coordinates).0);
+            }
+        }
+        // The same in u and v: every location past the rotated box shares
+        // its owner with every location further out along u or v
+        Metric::Chebyshev => {
+            let ((min_u, min_v), (max_u, max_v)) = rotated_box(input);
+            for y in grid.min.1..grid.min.1 + grid.height {
+                for x in grid.min.0..grid.min.0 + grid.width {
+                    let (u, v) = rotate((x, y));
+                    if u < min_u || u > max_u || v < min_v || v > max_v {
+                        infinite.insert(grid.get((x, y)).0);
+                    }
+                }
+            }
+        }
+        Metric::Euclidean => {
+            for (i, _) in voronoi::infinite(input).iter().enumerate().filter(|(_, infinite)| **infinite) {
+                infinite.insert(i as i32);
+            }
+        }
+    }
+
+    infinite
+}
+
+// Area of every input point's finite region
+fn finite_areas(input: &[(isize, isize)], method: Method) -> HashMap<i32, usize> {
+    let grid = regions(input, method);
+    let infinite = infinite_regions(input, method.metric(), &grid);
 
-    let item = freq.iter().max_by_key(|(_,v)|*v).unwrap();
+    grid.grid.iter()
+        .map(|(ordinal, _)| *ordinal)
+        .filter(|val| !infinite.contains(val))
+        .fold(HashMap::new(), |mut acc, val| { *acc.entry(val).or_insert(0) += 1; acc })
+}
+
+fn part_1(input: &[(isize, isize)], method: Method) {
+    let freq = finite_areas(input, method);
+
+    // Ties go to the point listed first in the input
+    let item = freq.iter().max_by_key(|(k,v)| (**v, -**k));
     println!("Frequencies {:?}", freq);
     println!("The winning item is {:?}", item);
 }
 
-fn valid(input: &Vec<(isize, isize)>, x: isize, y: isize) -> bool {
-    let max_distance = 10000;
-    input.iter()
-        .map(|(lx,ly)| ((lx-x).abs() + (ly-y).abs()) as usize)
-        .sum::<usize>() < max_distance
+#[derive(Debug)]
+struct SafeRegion {
+    size: usize,
+    // Top-left and bottom-right corners, if the region isn't empty
+    bounds: Option<((isize, isize), (isize, isize))>,
+}
+
+impl SafeRegion {
+    fn new() -> SafeRegion {
+        SafeRegion { size: 0, bounds: None }
+    }
+
+    fn add(&mut self, x: isize, y: isize) {
+        self.size += 1;
+        self.bounds = Some(match self.bounds {
+            None => ((x, y), (x, y)),
+            Some(((min_x, min_y), (max_x, max_y))) =>
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))),
+        });
+    }
+}
+
+// Total distance from any coordinate to all the given ones, along a single
+// axis. It's convex, smallest at the median.
+struct AxisDistances {
+    values: Vec<isize>,
+    // prefix[i] is the sum of the i smallest values
+    prefix: Vec<i64>,
+}
+
+impl AxisDistances {
+    fn new(values: &[isize]) -> AxisDistances {
+        let mut values = values.to_vec();
+        values.sort();
+        let mut prefix = vec![0];
+        for v in values.iter() {
+            let last = *prefix.last().unwrap();
+            prefix.push(last + *v as i64);
+        }
+        AxisDistances { values, prefix }
+    }
+
+    fn median(&self) -> isize {
+        self.values[self.values.len() / 2]
+    }
+
+    fn total(&self, at: isize) -> i64 {
+        let before = self.values.partition_point(|&v| v <= at);
+        let after = self.values.len() - before;
+        let at = at as i64;
+        (at * before as i64 - self.prefix[before]) + (self.prefix[self.values.len()] - self.prefix[before] - at * after as i64)
+    }
+
+    // The first coordinate in `from..=to` whose total is under `limit`, when
+    // the totals only shrink along that range, or only grow if `rising`
+    fn first_under(&self, from: isize, to: isize, limit: i64, rising: bool) -> isize {
+        let (mut low, mut high) = (from, to + 1);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if (self.total(mid) < limit) != rising { high = mid; } else { low = mid + 1; }
+        }
+        low
+    }
+}
+
+// Any location whose total distance is under the threshold must be closer
+// than threshold / n to some point, under any of the metrics, so the safe
+// region never extends further than that past the bounding box. Returns the
+// top-left and bottom-right corners of that area, if there's any safe
+// location at all.
+fn safe_area(input: &[(isize, isize)], threshold: i64) -> Option<((isize, isize), (isize, isize))> {
+    if threshold <= 0 || input.is_empty() {
+        return None;
+    }
+
+    let margin = (threshold / input.len() as i64 + 1) as isize;
+    let min_x = input.iter().map(|(x,_)|*x).min().unwrap();
+    let min_y = input.iter().map(|(_,y)|*y).min().unwrap();
+    let max_x = input.iter().map(|(x,_)|*x).max().unwrap();
+    let max_y = input.iter().map(|(_,y)|*y).max().unwrap();
+    Some(((min_x - margin, min_y - margin), (max_x + margin, max_y + margin)))
+}
+
+// Under Manhattan distance the total distance splits into a sum over x and a
+// sum over y, so a location is safe exactly when sum_x(x) + sum_y(y) is under
+// the threshold. For every safe x the safe y values form a range around the
+// median y, whose ends only move outward and then back inward as x goes
+// right, so the size is found in O(W + H) steps without visiting every
+// location, or allocating anything that large.
+fn manhattan_safe_region(input: &[(isize, isize)], threshold: i64) -> SafeRegion {
+    let ((min_x, min_y), (max_x, max_y)) = match safe_area(input, threshold) {
+        Some(area) => area,
+        None => return SafeRegion::new(),
+    };
+    let xs = AxisDistances::new(&input.iter().map(|(x,_)|*x).collect::<Vec<isize>>());
+    let ys = AxisDistances::new(&input.iter().map(|(_,y)|*y).collect::<Vec<isize>>());
+
+    // Both sums are convex, so the safe x values are a contiguous range
+    let (median_x, median_y) = (xs.median(), ys.median());
+    let limit = threshold - ys.total(median_y);
+    let first_x = xs.first_under(min_x, median_x, limit, false);
+    let last_x = xs.first_under(median_x, max_x, limit, true) - 1;
+
+    let mut region = SafeRegion::new();
+    // The safe y values for the current x are low..=high
+    let (mut low, mut high) = (median_y, median_y);
+    for x in first_x..=last_x {
+        let limit = threshold - xs.total(x);
+        if ys.total(median_y) >= limit {
+            (low, high) = (median_y, median_y);
+            continue;
+        }
+
+        while low > min_y && ys.total(low - 1) < limit { low -= 1; }
+        while ys.total(low) >= limit { low += 1; }
+        while high < max_y && ys.total(high + 1) < limit { high += 1; }
+        while ys.total(high) >= limit { high -= 1; }
+
+        region.size += (high - low + 1) as usize;
+        region.bounds = Some(match region.bounds {
+            None => ((x, low), (x, high)),
+            Some(((left, top), (_, bottom))) => ((left, top.min(low)), (x, bottom.max(high))),
+        });
+    }
+
+    region
 }
 
-fn part_2(input: &Vec<(isize, isize)>) {
-    let max_x = input.iter().map(|(x,_)|x).max().unwrap();
-    let max_y = input.iter().map(|(_,y)|y).max().unwrap();
+fn safe_region(input: &[(isize, isize)], metric: Metric, threshold: i64) -> SafeRegion {
+    if metric == Metric::Manhattan {
+        return manhattan_safe_region(input, threshold);
+    }
 
-    let mut counter = 0;
-    for i in 0..*max_x as isize {
-        for j in 0..*max_y as isize {
-            if valid(input, i,j) {
-                counter += 1;
+    let mut region = SafeRegion::new();
+    let ((min_x, min_y), (max_x, max_y)) = match safe_area(input, threshold) {
+        Some(area) => area,
+        None => return region,
+    };
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let total = input.iter()
+                .map(|point| metric.distance(*point, (x, y)))
+                .sum::<f64>();
+            if total < threshold as f64 {
+                region.add(x, y);
             }
         }
     }
+    region
+}
 
-    println!("Result: {}", counter);
+fn part_2(input: &[(isize, isize)], metric: Metric, threshold: i64) {
+    let region = safe_region(input, metric, threshold);
+    println!("Safe region bounds: {:?}", region.bounds);
+    println!("Result: {}", region.size);
 }
 
 fn main() {
     let input = read_input();
-    part_1(&input);
-    part_2(&input);
+    let mut method = Method::Flood(Neighbourhood::Four);
+    let mut threshold = 10000;
+    for arg in env::args().skip(1) {
+        match arg.parse::<i64>() {
+            Ok(value) => threshold = value,
+            Err(_) => method = Method::from_name(&arg)
+                .expect("expected a threshold or one of manhattan, chebyshev, euclidean, flood4 or flood8"),
+        }
+    }
+
+    part_1(&input, method);
+    part_2(&input, method.metric(), threshold);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn euclidean_region_past_the_box() {
+        // The region of (10, 1) is finite, but reaches down to y = -49
+        let input = [(0, 0), (20, 0), (10, 10), (10, 1)];
+        let areas = finite_areas(&input, Method::Nearest(Metric::Euclidean));
+        assert_eq!(areas.keys().collect::<Vec<&i32>>(), vec![&3]);
+    }
+
+    #[test]
+    fn chebyshev_matches_flood8() {
+        let input = [(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)];
+        let nearest = finite_areas(&input, Method::Nearest(Metric::Chebyshev));
+        let flood = finite_areas(&input, Method::Flood(Neighbourhood::Eight));
+        assert_eq!(nearest, flood);
+        assert!(!nearest.is_empty());
+    }
+
+    #[test]
+    fn manhattan_safe_region_matches_brute_force() {
+        let input = [(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)];
+        assert_eq!(manhattan_safe_region(&input, 32).size, 16);
+        for threshold in [-5, 0, 1, 30, 32, 60, 200, 1000] {
+            let mut expected = SafeRegion::new();
+            for y in -200..200 {
+                for x in -200..200 {
+                    let total = input.iter().map(|p| Metric::Manhattan.key(*p, (x, y))).sum::<i64>();
+                    if total < threshold { expected.add(x, y); }
+                }
+            }
+            let region = manhattan_safe_region(&input, threshold);
+            assert_eq!((region.size, region.bounds), (expected.size, expected.bounds), "threshold {}", threshold);
+        }
+    }
 }
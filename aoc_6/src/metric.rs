@@ -0,0 +1,86 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    Euclidean,
+}
+
+impl Metric {
+    pub fn from_name(name: &str) -> Option<Metric> {
+        match name {
+            "manhattan" => Some(Metric::Manhattan),
+            "chebyshev" => Some(Metric::Chebyshev),
+            "euclidean" => Some(Metric::Euclidean),
+            _ => None,
+        }
+    }
+
+    pub fn distance(&self, a: (isize, isize), b: (isize, isize)) -> f64 {
+        match *self {
+            Metric::Euclidean => (self.key(a, b) as f64).sqrt(),
+            _ => self.key(a, b) as f64,
+        }
+    }
+
+    // Orders pairs of points the same way `distance` does, but exactly, so
+    // that ties can be detected. For Euclidean that's the squared distance.
+    pub fn key(&self, a: (isize, isize), b: (isize, isize)) -> i64 {
+        let dx = (a.0 - b.0).abs() as i64;
+        let dy = (a.1 - b.1).abs() as i64;
+        match *self {
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => dx.max(dy),
+            Metric::Euclidean => dx * dx + dy * dy,
+        }
+    }
+}
+
+// Steps taken when growing areas by flood fill. Flooding with four neighbours
+// measures Manhattan distance, with eight it measures Chebyshev distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    Four,
+    Eight,
+}
+
+impl Neighbourhood {
+    pub fn offsets(&self) -> &'static [(isize, isize)] {
+        match *self {
+            Neighbourhood::Four => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+            Neighbourhood::Eight => &[(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)],
+        }
+    }
+
+    pub fn metric(&self) -> Metric {
+        match *self {
+            Neighbourhood::Four => Metric::Manhattan,
+            Neighbourhood::Eight => Metric::Chebyshev,
+        }
+    }
+}
+
+// How locations get assigned to the closest input point: either by growing
+// all the areas together one step at a time, or by measuring the distance to
+// every point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Flood(Neighbourhood),
+    Nearest(Metric),
+}
+
+impl Method {
+    pub fn from_name(name: &str) -> Option<Method> {
+        match name {
+            "flood4" => Some(Method::Flood(Neighbourhood::Four)),
+            "flood8" => Some(Method::Flood(Neighbourhood::Eight)),
+            _ => Metric::from_name(name).map(Method::Nearest),
+        }
+    }
+
+    pub fn metric(&self) -> Metric {
+        match *self {
+            Method::Flood(neighbourhood) => neighbourhood.metric(),
+            Method::Nearest(metric) => metric,
+        }
+    }
+}
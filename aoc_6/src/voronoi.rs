@@ -0,0 +1,91 @@
+// Exact geometry of the regions under Euclidean distance
+
+type Point = (isize, isize);
+
+fn cross(o: Point, a: Point, b: Point) -> i128 {
+    ((a.0 - o.0) as i128) * ((b.1 - o.1) as i128) - ((a.1 - o.1) as i128) * ((b.0 - o.0) as i128)
+}
+
+// Corners of the convex hull, counter-clockwise, leaving out points lying
+// along its edges
+fn hull(points: &[Point]) -> Vec<Point> {
+    let mut points = points.to_vec();
+    points.sort();
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut lower: Vec<Point> = Vec::new();
+    for &p in points.iter() {
+        while lower.len() >= 2 && cross(lower[lower.len() - 2], lower[lower.len() - 1], p) <= 0 {
+            lower.pop();
+        }
+        lower.push(p);
+    }
+    let mut upper: Vec<Point> = Vec::new();
+    for &p in points.iter().rev() {
+        while upper.len() >= 2 && cross(upper[upper.len() - 2], upper[upper.len() - 1], p) <= 0 {
+            upper.pop();
+        }
+        upper.push(p);
+    }
+
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+// Whether every point's region is infinite. A region is infinite exactly when
+// its point lies on the convex hull of all the points, on a corner or along
+// an edge: from anywhere strictly inside the hull there are points in every
+// direction, and going far enough in any direction gets closer to them.
+pub fn infinite(points: &[Point]) -> Vec<bool> {
+    let corners = hull(points);
+    if corners.len() < 3 {
+        // All the points are on one line
+        return vec![true; points.len()];
+    }
+
+    points.iter()
+        .map(|&p| (0..corners.len()).any(|i| cross(corners[i], corners[(i + 1) % corners.len()], p) <= 0))
+        .collect()
+}
+
+// Top-left and bottom-right corners of the locations the region of
+// `points[i]` could include, if that region is finite. The region is a convex
+// polygon whose corners are the centres of circles through the point and two
+// others, with no point inside.
+pub fn cell_bounds(points: &[Point], i: usize) -> (Point, Point) {
+    let p = points[i];
+    let others = points.iter()
+        .map(|&(x, y)| ((x - p.0) as i128, (y - p.1) as i128))
+        .filter(|&s| s != (0, 0))
+        .collect::<Vec<(i128, i128)>>();
+
+    let mut min = p;
+    let mut max = p;
+    for (j, &a) in others.iter().enumerate() {
+        for &b in others[j + 1..].iter() {
+            let d = 2 * (a.0 * b.1 - a.1 * b.0);
+            if d == 0 { continue; }
+
+            // The centre is p + (nx, ny) / d
+            let (a2, b2) = (a.0 * a.0 + a.1 * a.1, b.0 * b.0 + b.1 * b.1);
+            let (nx, ny, d) = (b.1 * a2 - a.1 * b2, a.0 * b2 - b.0 * a2, d);
+            let (nx, ny, d) = if d < 0 { (-nx, -ny, -d) } else { (nx, ny, d) };
+
+            // No other point can be closer to the centre than p
+            let is_corner = others.iter().all(|s| 2 * (nx * s.0 + ny * s.1) <= (s.0 * s.0 + s.1 * s.1) * d);
+            if !is_corner { continue; }
+
+            let floor = |n: i128| n.div_euclid(d) as isize;
+            let ceil = |n: i128| -((-n).div_euclid(d)) as isize;
+            min = (min.0.min(p.0 + floor(nx)), min.1.min(p.1 + floor(ny)));
+            max = (max.0.max(p.0 + ceil(nx)), max.1.max(p.1 + ceil(ny)));
+        }
+    }
+
+    (min, max)
+}
@@ -0,0 +1,83 @@
+use std::fmt::Write;
+use std::iter;
+
+use ::Edges;
+use ::NodesSet;
+use scheduler::Schedule;
+
+const GANTT_WIDTH: u32 = 100;
+
+// Render the dependency graph in Graphviz DOT format, with edges pointing from
+// each step to the steps it blocks
+pub fn to_dot(blocks: &Edges, nodes: &NodesSet) -> String {
+    let mut nodes = nodes.iter().collect::<Vec<&String>>();
+    nodes.sort();
+
+    let mut output = String::new();
+    writeln!(output, "digraph steps {{").unwrap();
+    for node in nodes {
+        writeln!(output, "    {:?};", node).unwrap();
+        let mut blocked = blocks.get(node).map_or(Vec::new(), |v| v.iter().collect::<Vec<&String>>());
+        blocked.sort();
+        for blocked_node in blocked {
+            writeln!(output, "    {:?} -> {:?};", node, blocked_node).unwrap();
+        }
+    }
+    writeln!(output, "}}").unwrap();
+    output
+}
+
+// Render the schedule as a text Gantt chart: one row per worker, one column
+// per `scale` seconds, each task drawn with its step name - repeated for
+// single letter names, padded with dashes otherwise. Tasks shorter than a
+// column still get one.
+pub fn gantt(schedule: &Schedule) -> String {
+    let total = schedule.total_time().max(1);
+    let scale = total.div_ceil(GANTT_WIDTH).max(1);
+    let columns = total.div_ceil(scale) as usize;
+
+    let mut axis = format!("{:>10}", "");
+    for column in (0..columns).step_by(10) {
+        write!(axis, "{:<10}", column as u32 * scale).unwrap();
+    }
+
+    let mut output = String::new();
+    writeln!(output, "{}", axis.trim_end()).unwrap();
+
+    for worker in 0..schedule.workers {
+        let mut row = vec!['.'; columns];
+        for task in schedule.tasks.iter().filter(|task| task.worker == worker) {
+            let start = ((task.start / scale) as usize).min(columns - 1);
+            let end = (task.end.div_ceil(scale) as usize).max(start + 1).min(columns);
+            let fill: Box<dyn Iterator<Item = char>> = if task.node.chars().count() == 1 {
+                Box::new(task.node.chars().cycle())
+            } else {
+                Box::new(task.node.chars().chain(iter::repeat('-')))
+            };
+            for (cell, c) in row[start..end].iter_mut().zip(fill) {
+                *cell = c;
+            }
+        }
+        writeln!(output, "worker {:<3}{}", worker, row.into_iter().collect::<String>()).unwrap();
+    }
+
+    writeln!(output, "{} seconds per column, {} seconds total", scale, schedule.total_time()).unwrap();
+    output
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn csv(schedule: &Schedule) -> String {
+    let mut output = String::new();
+    writeln!(output, "step,worker,start,end").unwrap();
+    for task in schedule.tasks.iter() {
+        writeln!(output, "{},{},{},{}", csv_field(&task.node), task.worker, task.start, task.end).unwrap();
+    }
+    output
+}
@@ -1,29 +1,76 @@
 #[macro_use] extern crate lazy_static;
 extern crate regex;
 
+mod export;
+mod scheduler;
+mod validate;
+
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
 use std::collections::HashSet;
 use std::collections::HashMap;
+use std::env;
+use std::process;
 use regex::Regex;
+use scheduler::Schedule;
+use validate::Stuck;
 
-fn read_input() -> Vec<(char, char)> {
-    let input_file = File::open("src/input.txt").unwrap();
-    let reader = BufReader::new(input_file);
+type Weights = HashMap<String, u32>;
 
+// Besides the dependencies, the input may give steps explicit durations with
+// lines like "Step compile takes 30 seconds."
+fn parse_input<I>(lines: I) -> (Vec<(String, String)>, Weights)
+    where I: Iterator<Item = String>
+{
     lazy_static! {
        static ref LINE_RE: Regex = Regex::new(r"Step (\S*) must be finished before step (\S*) can begin.").unwrap();
+       static ref WEIGHT_RE: Regex = Regex::new(r"Step (\S*) takes (\d+) seconds?.").unwrap();
+    }
+
+    let mut edges = Vec::new();
+    let mut weights = Weights::new();
+    for line in lines {
+        if let Some(cap) = LINE_RE.captures(&line) {
+            edges.push((cap[1].to_string(), cap[2].to_string()));
+        } else if let Some(cap) = WEIGHT_RE.captures(&line) {
+            weights.insert(cap[1].to_string(), cap[2].parse::<u32>().unwrap());
+        }
     }
 
-    reader.lines()
-        .flat_map(|line|line.ok())
-        .flat_map(|line|LINE_RE.captures(&line).map(|cap| (cap[1].chars().nth(0).unwrap(), cap[2].chars().nth(0).unwrap())))
-        .collect()
+    (edges, weights)
+}
+
+fn read_input() -> (Vec<(String, String)>, Weights) {
+    let input_file = File::open("src/input.txt").unwrap();
+    let reader = BufReader::new(input_file);
+    parse_input(reader.lines().map(|line|line.unwrap()))
+}
+
+// One "name duration" pair per line
+fn read_weights(path: &str) -> Result<Weights, String> {
+    let input_file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let reader = BufReader::new(input_file);
+    let mut weights = Weights::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("{}: {}", path, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [name, duration] => match duration.parse::<u32>() {
+                Ok(duration) => { weights.insert(name.to_string(), duration); }
+                Err(_) => return Err(format!("{}:{}: invalid duration {:?}", path, i + 1, duration)),
+            },
+            _ => return Err(format!("{}:{}: expected \"name duration\", got {:?}", path, i + 1, line)),
+        }
+    }
+    Ok(weights)
 }
 
-type NodesSet = HashSet<char>;
-type Edges = HashMap<char, NodesSet>;
+type NodesSet = HashSet<String>;
+type Edges = HashMap<String, NodesSet>;
 
 // Transform the raw input into a graph representation.
 // Return value is a tuple:
@@ -32,19 +79,19 @@ type Edges = HashMap<char, NodesSet>;
 // 2nd element is "Blocked By" - associating each node with the set
 // of node that is directly blocking it
 // 3rd element is a set of the nodes in the graph
-fn init_graph(input: Vec<(char, char)>) -> (Edges, Edges, NodesSet) {
+fn init_graph(input: Vec<(String, String)>) -> (Edges, Edges, NodesSet) {
     let mut blocks = Edges::new();
     let mut blocked_by = Edges::new();
 
     for (blocker, blocked) in input.iter() {
-        blocks.entry(*blocker).or_insert(HashSet::new()).insert(*blocked);
-        blocked_by.entry(*blocked).or_insert(HashSet::new()).insert(*blocker);
+        blocks.entry(blocker.clone()).or_default().insert(blocked.clone());
+        blocked_by.entry(blocked.clone()).or_default().insert(blocker.clone());
     }
 
     let nodes = blocks.keys()
         .chain(blocked_by.keys())
-        .map(|x|(*x).clone())
-        .collect::<HashSet<char>>();
+        .cloned()
+        .collect::<NodesSet>();
 
     (blocks, blocked_by, nodes)
 }
@@ -55,95 +102,95 @@ fn find_ready(blocked_by: &Edges, nodes: NodesSet) -> NodesSet {
         .collect::<NodesSet>()
 }
 
-fn finish_procesing(node: char, blocks: &Edges, blocked_by: &mut Edges, ready: &mut NodesSet) {
-    for blocked_node in blocks.get(&node).unwrap_or(&HashSet::new()) {
+fn finish_procesing(node: &str, blocks: &Edges, blocked_by: &mut Edges, ready: &mut NodesSet) {
+    for blocked_node in blocks.get(node).unwrap_or(&HashSet::new()) {
         if blocked_by
                 .get_mut(blocked_node)
                 .map_or(0, |v| {
-                    v.remove(&node);
+                    v.remove(node);
                     v.len()
                 }) == 0 {
-            ready.insert((*blocked_node).clone());
+            ready.insert(blocked_node.clone());
             blocked_by.remove(blocked_node);
         }
     }
 }
 
-fn part1(blocks: &Edges, mut blocked_by: Edges, nodes: NodesSet) {
-    let mut ready = find_ready(&blocked_by, nodes);
-
-    let mut result = Vec::new();
-    while !ready.is_empty() {
-        let current = ready.iter().min().unwrap().clone();
-        ready.remove(&current);
-        result.push(current);
+// With a single worker nodes are done one at a time, always picking the
+// lexicographically first ready one, whatever the durations
+fn part1(blocks: &Edges, blocked_by: Edges, nodes: NodesSet) -> Result<String, Stuck> {
+    scheduler::schedule(blocks, blocked_by, nodes, 1, |_| 1).map(|schedule| schedule.order())
+}
 
-        finish_procesing(current, &blocks, &mut blocked_by, &mut ready);
+// Steps without an explicit duration take `base_time` plus their letter's
+// position in the alphabet for single letter names, or plus one otherwise
+fn duration(node: &str, weights: &Weights, base_time: u32) -> u32 {
+    if let Some(&weight) = weights.get(node) {
+        return weight;
     }
 
-    println!("Result: {}", result.iter().collect::<String>());
+    let mut chars = node.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_uppercase() => base_time + (c as u32 - 'A' as u32) + 1,
+        _ => base_time + 1,
+    }
 }
 
-fn part2(blocks: &Edges, mut blocked_by: Edges, nodes: NodesSet) {
-    let mut ready = find_ready(&blocked_by, nodes);
-
-    let mut workers = vec![None; 5];
-    let mut result = Vec::new();
-
-    let base_time = 60;
-    let mut total_time = 0;
-
-    loop {
-        // Exit condition - no nodes currently under processing and
-        // no more ready nodes - no action can be taken so we must be done
-        // (ideally we'll confirm all nodes were processed, but...)
-        if ready.is_empty() && workers.iter().all(|x|x.is_none()) {
-            break;
-        }
+fn part2(blocks: &Edges, blocked_by: Edges, nodes: NodesSet, workers: usize, base_time: u32, weights: &Weights) -> Result<Schedule, Stuck> {
+    scheduler::schedule(blocks, blocked_by, nodes, workers, |node| duration(node, weights, base_time))
+}
 
-        // Assign - if there are ready nodes and idle workers, then
-        // assign them the next ready nodes
-        if !ready.is_empty() && workers.iter().any(|x|x.is_none()) {
-            let mut sorted_ready = ready.iter()
-                .map(|x|*x)
-                .collect::<Vec<char>>();
-            sorted_ready.sort();
-
-            for (node, worker) in sorted_ready.into_iter()
-                .zip(workers.iter_mut().filter(|x|x.is_none())) {
-                    let time = base_time + (node as u32 - 'A' as u32) + 1;
-                    *worker = Some((node, time));
-                    ready.remove(&node);
-                }
+// Usage: aoc_7 [dot|gantt|csv] [--weights FILE] [WORKERS] [BASE_TIME]
+fn main() {
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let weights_file = args.iter().position(|x| x == "--weights").map(|i| {
+        if i + 1 == args.len() {
+            eprintln!("Usage: aoc_7 [dot|gantt|csv] [--weights FILE] [WORKERS] [BASE_TIME]");
+            process::exit(1);
         }
-
-        // Advance - advance the time until the next node becomes ready
-        let next_step = workers.iter().flat_map(|x|x.map(|y|y.1)).min().unwrap();
-        total_time += next_step;
-
-        // Collect any finished work, updating 'ready' accordingly
-        for item in workers.iter_mut().filter(|x|x.is_some()) {
-            item.as_mut().unwrap().1 -= next_step;
-            //println!("item2: {:?}", item);
-            if item.unwrap().1 == 0 {
-                let node = item.unwrap().0;
-                result.push(node);
-                *item = None;
-                finish_procesing(node, &blocks, &mut blocked_by, &mut ready);
+        args.remove(i);
+        args.remove(i)
+    });
+    let command = match args.first().map(|x| x.as_str()) {
+        Some("dot") | Some("gantt") | Some("csv") => Some(args.remove(0)),
+        _ => None,
+    };
+    let workers = args.first().map_or(5, |x| x.parse::<usize>().unwrap());
+    let base_time = args.get(1).map_or(60, |x| x.parse::<u32>().unwrap());
+
+    let (input, mut weights) = read_input();
+
+    // Steps may also be known only from their duration in the input. A
+    // weights file only gives durations to steps that are already known.
+    let (blocks, blocked_by, mut nodes) = init_graph(input);
+    nodes.extend(weights.keys().cloned());
+    if let Some(path) = weights_file {
+        let file_weights = read_weights(&path).unwrap_or_else(|e| {
+            eprintln!("Invalid weights file: {}", e);
+            process::exit(1);
+        });
+        for (node, weight) in file_weights {
+            if nodes.contains(&node) {
+                weights.insert(node, weight);
+            } else {
+                eprintln!("Ignoring weight for unknown step {:?}", node);
             }
         }
     }
 
-    println!("Result: {} took {}", result.iter().collect::<String>(), total_time);
-}
+    if let Err(stuck) = validate::check(&blocks, &blocked_by, &nodes) {
+        eprintln!("Invalid graph: {}", stuck);
+        process::exit(1);
+    }
 
-fn main() {
-    let input = read_input();
-    let (blocks, blocked_by, nodes) = init_graph(input);
-    println!("Nodes: {:?}", nodes);
-    println!("Blockers: {:?}", blocks);
-    println!("Blocked: {:?}", blocked_by);
-
-    part1(&blocks, blocked_by.clone(), nodes.clone());
-    part2(&blocks, blocked_by, nodes);
+    match command.as_deref() {
+        Some("dot") => print!("{}", export::to_dot(&blocks, &nodes)),
+        Some("gantt") => print!("{}", export::gantt(&part2(&blocks, blocked_by, nodes, workers, base_time, &weights).unwrap())),
+        Some("csv") => print!("{}", export::csv(&part2(&blocks, blocked_by, nodes, workers, base_time, &weights).unwrap())),
+        _ => {
+            println!("Result: {}", part1(&blocks, blocked_by.clone(), nodes.clone()).unwrap());
+            let schedule = part2(&blocks, blocked_by, nodes, workers, base_time, &weights).unwrap();
+            println!("Result: {} took {} with {} workers", schedule.order(), schedule.total_time(), schedule.workers);
+        }
+    }
 }
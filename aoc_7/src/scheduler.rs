@@ -0,0 +1,84 @@
+use ::Edges;
+use ::NodesSet;
+use ::find_ready;
+use ::finish_procesing;
+use validate;
+use validate::Stuck;
+
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub worker: usize,
+    pub node: String,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug)]
+pub struct Schedule {
+    pub workers: usize,
+    // In the order the tasks were finished
+    pub tasks: Vec<Task>,
+}
+
+impl Schedule {
+    // Single letter step names are simply concatenated, longer ones are
+    // separated by spaces
+    pub fn order(&self) -> String {
+        let separator = if self.tasks.iter().all(|task| task.node.chars().count() == 1) { "" } else { " " };
+        self.tasks.iter().map(|task| task.node.as_str()).collect::<Vec<&str>>().join(separator)
+    }
+
+    pub fn total_time(&self) -> u32 {
+        self.tasks.iter().map(|task| task.end).max().unwrap_or(0)
+    }
+}
+
+// Process the graph with a pool of `workers`, each taking the lexicographically
+// first ready node whenever it's idle. Idle workers are assigned in order, so
+// a lower-numbered worker always gets the earlier node. Fails if some of the
+// nodes could never be processed.
+pub fn schedule<F>(blocks: &Edges, mut blocked_by: Edges, nodes: NodesSet, workers: usize, duration: F) -> Result<Schedule, Stuck>
+    where F: Fn(&str) -> u32
+{
+    assert!(workers > 0, "need at least one worker");
+    validate::check(blocks, &blocked_by, &nodes)?;
+
+    let mut ready = find_ready(&blocked_by, nodes);
+
+    let mut pool: Vec<Option<Task>> = vec![None; workers];
+    let mut tasks = Vec::new();
+    let mut time = 0;
+
+    loop {
+        // Exit condition - no nodes currently under processing and
+        // no more ready nodes - no action can be taken so we must be done
+        if ready.is_empty() && pool.iter().all(|x|x.is_none()) {
+            break;
+        }
+
+        // Assign - if there are ready nodes and idle workers, then
+        // assign them the next ready nodes
+        let mut sorted_ready = ready.iter().cloned().collect::<Vec<String>>();
+        sorted_ready.sort();
+
+        for (node, (worker, slot)) in sorted_ready.into_iter()
+            .zip(pool.iter_mut().enumerate().filter(|(_, x)|x.is_none())) {
+                ready.remove(&node);
+                *slot = Some(Task { worker, start: time, end: time + duration(&node), node });
+            }
+
+        // Advance - advance the time until the next node is done
+        time = pool.iter().flatten().map(|task| task.end).min().unwrap();
+
+        // Collect any finished work, updating 'ready' accordingly
+        for slot in pool.iter_mut() {
+            if slot.as_ref().is_some_and(|task| task.end == time) {
+                let task = slot.take().unwrap();
+                finish_procesing(&task.node, blocks, &mut blocked_by, &mut ready);
+                tasks.push(task);
+            }
+        }
+    }
+
+    Ok(Schedule { workers, tasks })
+}
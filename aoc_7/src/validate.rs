@@ -0,0 +1,64 @@
+use std::fmt;
+
+use ::Edges;
+use ::NodesSet;
+use ::find_ready;
+use ::finish_procesing;
+
+// Steps that can never be processed: the nodes of one of the cycles in the
+// graph, and every other step that isn't ready because of some cycle
+#[derive(Debug)]
+pub struct Stuck {
+    // Starts and ends with the same step, each one blocking the next
+    pub cycle: Vec<String>,
+    pub never_ready: Vec<String>,
+}
+
+impl fmt::Display for Stuck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cycle {}, steps never ready: {}", self.cycle.join(" -> "), self.never_ready.join(", "))
+    }
+}
+
+// Walk the graph the same way the scheduler does, and report what couldn't be
+// reached if it gets stuck
+pub fn check(blocks: &Edges, blocked_by: &Edges, nodes: &NodesSet) -> Result<(), Stuck> {
+    let mut remaining_blocked_by = blocked_by.clone();
+    let mut remaining = nodes.clone();
+    let mut ready = find_ready(&remaining_blocked_by, nodes.clone());
+
+    while let Some(node) = ready.iter().next().cloned() {
+        ready.remove(&node);
+        remaining.remove(&node);
+        finish_procesing(&node, blocks, &mut remaining_blocked_by, &mut ready);
+    }
+
+    if remaining.is_empty() {
+        return Ok(());
+    }
+
+    // Every remaining step still has a remaining blocker, so following
+    // blockers back from any of them has to run into a step seen before
+    let mut path = vec![remaining.iter().min().unwrap().clone()];
+    let start = loop {
+        let blocker = remaining_blocked_by[path.last().unwrap()].iter()
+            .filter(|x| remaining.contains(*x))
+            .min()
+            .unwrap()
+            .clone();
+        if let Some(start) = path.iter().position(|x| *x == blocker) {
+            break start;
+        }
+        path.push(blocker);
+    };
+
+    let mut cycle = path.split_off(start);
+    cycle.reverse();
+    let first = cycle[0].clone();
+    cycle.push(first);
+
+    let mut never_ready = remaining.into_iter().collect::<Vec<String>>();
+    never_ready.sort();
+
+    Err(Stuck { cycle, never_ready })
+}
@@ -0,0 +1,54 @@
+use std::fmt::Write;
+
+use ::TreeNode;
+
+// Flatten the tree back into the license format: a header with the number of
+// children and metadata entries, the children, and then the metadata entries
+pub fn encode(tree: &TreeNode) -> Vec<usize> {
+    let mut output = Vec::new();
+    // Each node along with the number of its children written so far
+    let mut stack = vec![(tree, 0)];
+    output.push(tree.children.len());
+    output.push(tree.metadata.len());
+
+    while let Some((node, written)) = stack.pop() {
+        match node.children.get(written) {
+            Some(child) => {
+                stack.push((node, written + 1));
+                stack.push((child, 0));
+                output.push(child.children.len());
+                output.push(child.metadata.len());
+            }
+            None => output.extend_from_slice(&node.metadata),
+        }
+    }
+
+    output
+}
+
+pub fn to_string(tree: &TreeNode) -> String {
+    encode(tree).iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+// One line per node, indented by depth and labeled with the node's path, e.g.
+//
+//   0: [1 1 2]
+//     0.0: [10 11 12]
+//     0.1: [2]
+//       0.1.0: [99]
+pub fn pretty(tree: &TreeNode) -> String {
+    let mut output = String::new();
+    let mut stack = vec![(tree, "0".to_string())];
+
+    while let Some((node, path)) = stack.pop() {
+        let depth = path.matches('.').count();
+        let metadata = node.metadata.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+        writeln!(output, "{:indent$}{}: [{}]", "", path, metadata.join(" "), indent = depth * 2).unwrap();
+
+        for (i, child) in node.children.iter().enumerate().rev() {
+            stack.push((child, format!("{}.{}", path, i)));
+        }
+    }
+
+    output
+}
@@ -0,0 +1,99 @@
+use std::convert::Infallible;
+
+use ::TreeNode;
+
+// What the evaluator sees of every node: the node itself, its path from the
+// root (as child indexes) and the values already computed for its children
+pub struct Visit<'a, T: 'a> {
+    pub node: &'a TreeNode,
+    pub path: &'a [usize],
+    pub children: &'a [T],
+}
+
+// Evaluate the tree bottom-up, visiting every node exactly once after all of
+// its children. Each child's value is computed once and handed to the parent,
+// which may refer to it any number of times. Stops at the first error.
+pub fn try_fold<T, E, F>(tree: &TreeNode, mut f: F) -> Result<T, E>
+    where F: FnMut(Visit<T>) -> Result<T, E>
+{
+    // The path from the root to the current node, each along with the
+    // values of the children evaluated so far
+    let mut frames: Vec<(&TreeNode, Vec<T>)> = vec![(tree, Vec::with_capacity(tree.children.len()))];
+    let mut path = Vec::new();
+
+    loop {
+        let (node, done) = {
+            let top = frames.last().unwrap();
+            (top.0, top.1.len())
+        };
+
+        if let Some(child) = node.children.get(done) {
+            path.push(done);
+            frames.push((child, Vec::with_capacity(child.children.len())));
+            continue;
+        }
+
+        let (node, children) = frames.pop().unwrap();
+        let value = f(Visit { node, path: &path, children: &children })?;
+        path.pop();
+
+        match frames.last_mut() {
+            Some(parent) => parent.1.push(value),
+            None => return Ok(value),
+        }
+    }
+}
+
+pub fn fold<T, F>(tree: &TreeNode, mut f: F) -> T
+    where F: FnMut(Visit<T>) -> T
+{
+    match try_fold(tree, |visit| Ok::<T, Infallible>(f(visit))) {
+        Ok(value) => value,
+        Err(never) => match never {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::testdata::{EXAMPLE, deep_input};
+    use ::build_tree;
+
+    #[test]
+    fn visits_children_first_with_paths() {
+        let tree = build_tree(&EXAMPLE).unwrap();
+        let mut order = Vec::new();
+        let count = fold(&tree, |visit| {
+            order.push((visit.path.to_vec(), visit.children.to_vec()));
+            1 + visit.children.iter().sum::<usize>()
+        });
+
+        assert_eq!(count, 4);
+        assert_eq!(order, vec![
+            (vec![0], vec![]),
+            (vec![1, 0], vec![]),
+            (vec![1], vec![1]),
+            (vec![], vec![1, 2]),
+        ]);
+    }
+
+    #[test]
+    fn stops_at_first_error() {
+        let tree = build_tree(&EXAMPLE).unwrap();
+        let mut visited = 0;
+        let result = try_fold(&tree, |visit| {
+            visited += 1;
+            if visit.node.metadata == [99] { Err(visit.path.to_vec()) } else { Ok(()) }
+        });
+
+        assert_eq!(result, Err(vec![1, 0]));
+        assert_eq!(visited, 2);
+    }
+
+    #[test]
+    fn deep_tree() {
+        let depth = 1_000_000;
+        let tree = build_tree(&deep_input(depth)).unwrap();
+        assert_eq!(fold(&tree, |visit| visit.children.first().map_or(1, |depth| depth + 1)), depth);
+    }
+}
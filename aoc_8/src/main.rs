@@ -1,19 +1,34 @@
+mod codec;
+mod fold;
+mod query;
+#[cfg(test)]
+mod testdata;
+
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
+use std::env;
+use std::fmt;
+use std::mem;
+use std::process;
 
-fn read_input() -> Vec<usize> {
+fn read_input() -> Result<Vec<usize>, ParseError> {
     let input_file = File::open("src/input.txt").unwrap();
     let reader = BufReader::new(input_file);
-    reader.lines()
-        .flat_map(|line|line.ok())
-        .map(|line|
-             line
-                .split_whitespace()
-                .flat_map(|s| s.parse::<usize>())
-                .collect::<Vec<usize>>())
-        .nth(0)
-        .unwrap()
+    parse_numbers(reader.lines().map(|line|line.unwrap()))
+}
+
+// The whitespace separated numbers of the input, in order
+fn parse_numbers<I: Iterator<Item = String>>(lines: I) -> Result<Vec<usize>, ParseError> {
+    let mut numbers = Vec::new();
+    for line in lines {
+        for token in line.split_whitespace() {
+            let number = token.parse::<usize>()
+                .map_err(|_| ParseError::NotANumber { offset: numbers.len(), token: token.to_string() })?;
+            numbers.push(number);
+        }
+    }
+    Ok(numbers)
 }
 
 type MetadataEntry = usize;
@@ -24,50 +39,249 @@ struct TreeNode {
     metadata: Vec<MetadataEntry>,
 }
 
-fn build_tree(current: &mut std::slice::Iter<usize>) -> TreeNode {
-    let num_children = current.next().unwrap();
-    let num_metadata = current.next().unwrap();
-
-    let mut children = Vec::with_capacity(*num_children);
-    let mut metadata = Vec::with_capacity(*num_metadata);
+// Dropping the children one level at a time would recurse as deep as the
+// tree goes, so flatten them out first
+impl Drop for TreeNode {
+    fn drop(&mut self) {
+        let mut pending = mem::take(&mut self.children);
+        while let Some(mut node) = pending.pop() {
+            pending.append(&mut node.children);
+        }
+    }
+}
 
-    println!("New node: {} children {} MD", num_children, num_metadata);
+#[derive(Debug)]
+enum ParseError {
+    // The input ended at `offset` while reading `expected`
+    Truncated { offset: usize, expected: &'static str },
+    // The tree was complete at `offset` but `count` more numbers followed
+    Trailing { offset: usize, count: usize },
+    // The token that should have been the number at `offset` isn't one
+    NotANumber { offset: usize, token: String },
+}
 
-    for _i in 0..*num_children {
-        children.push(build_tree(current));
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated { offset, expected } =>
+                write!(f, "input ended at offset {} while reading {}", offset, expected),
+            ParseError::Trailing { offset, count } =>
+                write!(f, "tree ends at offset {} but {} more numbers follow", offset, count),
+            ParseError::NotANumber { offset, token } =>
+                write!(f, "expected a number at offset {}, got {:?}", offset, token),
+        }
     }
+}
 
-    for _i in 0..*num_metadata {
-        metadata.push(*current.next().unwrap());
+// A node whose children are still being read
+struct PartialNode {
+    num_children: usize,
+    num_metadata: usize,
+    children: Vec<TreeNode>,
+}
+
+// Parse the tree keeping the path from the root to the node being read on an
+// explicit stack, so that the depth of the tree is only bounded by memory
+fn build_tree(input: &[usize]) -> Result<TreeNode, ParseError> {
+    let mut offset = 0;
+    let mut stack: Vec<PartialNode> = Vec::new();
+
+    let read_header = |offset: &mut usize| -> Result<PartialNode, ParseError> {
+        let num_children = *input.get(*offset).ok_or(ParseError::Truncated { offset: *offset, expected: "child count" })?;
+        let num_metadata = *input.get(*offset + 1).ok_or(ParseError::Truncated { offset: *offset + 1, expected: "metadata count" })?;
+        *offset += 2;
+
+        // Don't trust the header with allocations: every child takes at
+        // least two more numbers
+        let capacity = num_children.min((input.len() - *offset) / 2);
+        Ok(PartialNode { num_children, num_metadata, children: Vec::with_capacity(capacity) })
+    };
+
+    stack.push(read_header(&mut offset)?);
+    let root = loop {
+        let top = stack.last_mut().unwrap();
+        if top.children.len() < top.num_children {
+            let child = read_header(&mut offset)?;
+            stack.push(child);
+            continue;
+        }
+
+        if input.len() - offset < top.num_metadata {
+            return Err(ParseError::Truncated { offset: input.len(), expected: "metadata" });
+        }
+        let metadata = input[offset..offset + top.num_metadata].to_vec();
+        offset += top.num_metadata;
+
+        let done = stack.pop().unwrap();
+        let node = TreeNode { children: done.children, metadata };
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => break node,
+        }
+    };
+
+    if offset < input.len() {
+        return Err(ParseError::Trailing { offset, count: input.len() - offset });
     }
 
-    TreeNode{children, metadata}
+    Ok(root)
 }
 
 fn traverse(tree: &TreeNode) -> usize {
-    tree.children.iter().map(|c| traverse(c)).sum::<usize>() +
-        tree.metadata.iter().sum::<usize>()
+    fold::fold(tree, |visit| {
+        visit.children.iter().sum::<usize>() + visit.node.metadata.iter().sum::<usize>()
+    })
+}
+
+// What part 2 does with metadata entries that don't refer to any child: 0,
+// or anything past the number of children. The puzzle skips them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IndexPolicy {
+    Skip,
+    Reject,
 }
 
-fn traverse2(tree: &TreeNode) -> usize {
-    if tree.children.len() == 0 {
-        return tree.metadata.iter().sum::<usize>();
+#[derive(Debug)]
+struct IndexError {
+    path: Vec<usize>,
+    entry: MetadataEntry,
+    children: usize,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "node {}: metadata entry {} doesn't refer to any of its {} children",
+            query::format_path(&self.path), self.entry, self.children)
     }
+}
 
-    // We have children, so use 'metadata' as an indexer
-    tree.metadata
-        .iter()
-        .flat_map(|idx| tree.children.get(idx-1))
-        .map(|node|traverse2(node))
-        .sum::<usize>()
+// Metadata entries refer to children starting at 1
+fn child_index(entry: MetadataEntry, children: usize) -> Option<usize> {
+    if entry >= 1 && entry <= children { Some(entry - 1) } else { None }
+}
+
+fn traverse2(tree: &TreeNode, policy: IndexPolicy) -> Result<usize, IndexError> {
+    fold::try_fold(tree, |visit| {
+        if visit.children.is_empty() {
+            return Ok(visit.node.metadata.iter().sum::<usize>());
+        }
+
+        // We have children, so use 'metadata' as an indexer
+        visit.node.metadata
+            .iter()
+            .map(|&entry| match child_index(entry, visit.children.len()) {
+                Some(idx) => Ok(visit.children[idx]),
+                None if policy == IndexPolicy::Skip => Ok(0),
+                None => Err(IndexError { path: visit.path.to_vec(), entry, children: visit.children.len() }),
+            })
+            .sum::<Result<usize, IndexError>>()
+    })
 }
 
 fn main() {
-    let input = read_input();
-    println!("Input {:?}", input);
+    let tree = match read_input().and_then(|input| build_tree(&input)) {
+        Ok(tree) => tree,
+        Err(e) => {
+            eprintln!("Invalid input: {}", e);
+            process::exit(1);
+        }
+    };
 
-    let tree = build_tree(&mut input.iter());
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let policy = match args.iter().position(|x| x == "--strict") {
+        Some(i) => {
+            args.remove(i);
+            IndexPolicy::Reject
+        }
+        None => IndexPolicy::Skip,
+    };
+    match args.first().map(|x| x.as_str()) {
+        Some("encode") => println!("{}", codec::to_string(&tree)),
+        Some("pretty") => print!("{}", codec::pretty(&tree)),
+        Some("query") => {
+            let path = args.get(1).map_or("0", |x| x.as_str());
+            let node = query::parse_path(path)
+                .and_then(|indexes| query::node_at(&tree, &indexes).ok_or(format!("no node at {}", path)));
+            match node {
+                Ok(node) => {
+                    println!("Node {}", path);
+                    println!("  Depth: {}", path.matches('.').count());
+                    println!("  Children: {}", node.children.len());
+                    println!("  Metadata: {:?}", node.metadata);
+                    println!("  Subtree size: {}", query::size(node));
+                    println!("  Subtree height: {}", query::height(node));
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        _ => {
+            println!("Part 1: Tree {:?}", traverse(&tree));
+            match traverse2(&tree, policy) {
+                Ok(value) => println!("Part 2: Tree {:?}", value),
+                Err(e) => {
+                    eprintln!("Part 2: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+}
 
-    println!("Part 1: Tree {:?}", traverse(&tree));
-    println!("Part 2: Tree {:?}", traverse2(&tree));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testdata::{EXAMPLE, deep_input};
+
+    #[test]
+    fn example() {
+        let tree = build_tree(&EXAMPLE).unwrap();
+        assert_eq!(tree.metadata, vec![1, 1, 2]);
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.children[1].children[0].metadata, vec![99]);
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(matches!(build_tree(&[]), Err(ParseError::Truncated { offset: 0, expected: "child count" })));
+        assert!(matches!(build_tree(&[1]), Err(ParseError::Truncated { offset: 1, expected: "metadata count" })));
+        assert!(matches!(build_tree(&[1, 1]), Err(ParseError::Truncated { offset: 2, expected: "child count" })));
+        assert!(matches!(build_tree(&[0, 3, 1, 2]), Err(ParseError::Truncated { offset: 4, expected: "metadata" })));
+        assert!(matches!(build_tree(&EXAMPLE[..15]), Err(ParseError::Truncated { offset: 15, expected: "metadata" })));
+    }
+
+    #[test]
+    fn trailing() {
+        assert!(matches!(build_tree(&[0, 1, 5, 7, 8]), Err(ParseError::Trailing { offset: 3, count: 2 })));
+        let mut input = EXAMPLE.to_vec();
+        input.push(0);
+        assert!(matches!(build_tree(&input), Err(ParseError::Trailing { offset: 16, count: 1 })));
+    }
+
+    #[test]
+    fn not_a_number() {
+        let lines = vec!["2 3 0 3".to_string(), "".to_string(), "10 x1 12".to_string()];
+        match parse_numbers(lines.into_iter()) {
+            Err(ParseError::NotANumber { offset: 5, ref token }) => assert_eq!(token, "x1"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(parse_numbers(vec!["1 -2".to_string()].into_iter()), Err(ParseError::NotANumber { offset: 1, .. })));
+        assert_eq!(parse_numbers(vec![" 1  2 ".to_string(), "3".to_string()].into_iter()).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn huge_child_count() {
+        // The header claims more children than the input could hold
+        assert!(matches!(build_tree(&[usize::MAX, 0, 0, 0]), Err(ParseError::Truncated { offset: 4, expected: "child count" })));
+    }
+
+    #[test]
+    fn deep_tree() {
+        let depth = 1_000_000;
+        let tree = build_tree(&deep_input(depth)).unwrap();
+        assert_eq!(tree.metadata, vec![1]);
+        assert_eq!(query::height(&tree), depth);
+    }
 }
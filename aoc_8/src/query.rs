@@ -0,0 +1,46 @@
+use ::TreeNode;
+
+// Parse a node path like "0.2.1": the root is "0", and every following number
+// is the 0-based index of a child of the previous node. Returns the child
+// indexes only.
+pub fn parse_path(path: &str) -> Result<Vec<usize>, String> {
+    let mut parts = path.split('.');
+    if parts.next() != Some("0") {
+        return Err(format!("path {:?} should start at the root, \"0\"", path));
+    }
+
+    parts.map(|part| part.parse::<usize>().map_err(|_| format!("invalid child index {:?} in {:?}", part, path)))
+        .collect()
+}
+
+pub fn format_path(path: &[usize]) -> String {
+    let mut parts = vec!["0".to_string()];
+    parts.extend(path.iter().map(|i| i.to_string()));
+    parts.join(".")
+}
+
+pub fn node_at<'a>(tree: &'a TreeNode, path: &[usize]) -> Option<&'a TreeNode> {
+    path.iter().try_fold(tree, |node, &i| node.children.get(i))
+}
+
+// Number of nodes in the subtree, including its root
+pub fn size(tree: &TreeNode) -> usize {
+    let mut count = 0;
+    let mut stack = vec![tree];
+    while let Some(node) = stack.pop() {
+        count += 1;
+        stack.extend(node.children.iter());
+    }
+    count
+}
+
+// Number of levels in the subtree: 1 for a leaf
+pub fn height(tree: &TreeNode) -> usize {
+    let mut max = 0;
+    let mut stack = vec![(tree, 1)];
+    while let Some((node, depth)) = stack.pop() {
+        max = max.max(depth);
+        stack.extend(node.children.iter().map(|child| (child, depth + 1)));
+    }
+    max
+}
@@ -0,0 +1,16 @@
+// Inputs shared by the tests
+
+// The puzzle's example tree
+pub const EXAMPLE: [usize; 16] = [2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2];
+
+// A chain of `depth` nodes, each with a single child and a single metadata
+// entry of 1
+pub fn deep_input(depth: usize) -> Vec<usize> {
+    let mut input = Vec::with_capacity(depth * 3);
+    for _ in 0..depth - 1 {
+        input.extend_from_slice(&[1, 1]);
+    }
+    input.extend_from_slice(&[0, 1]);
+    input.extend(::std::iter::repeat_n(1, depth));
+    input
+}
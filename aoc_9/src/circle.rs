@@ -0,0 +1,148 @@
+struct Node<T> {
+    value: Option<T>,
+    prev: usize,
+    next: usize,
+}
+
+// A circular sequence with a cursor, kept as a doubly linked list inside a
+// `Vec`. Moving the cursor one step, inserting after it and removing at it are
+// all O(1). Slots of removed items are reused by later insertions, so memory
+// only grows with the largest number of items held at once.
+pub struct Circle<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+    current: usize,
+    len: usize,
+}
+
+impl<T> Circle<T> {
+    pub fn with_capacity(capacity: usize) -> Circle<T> {
+        Circle { nodes: Vec::with_capacity(capacity), free: Vec::new(), current: 0, len: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Move the cursor `steps` items clockwise, or counter-clockwise if negative
+    pub fn rotate(&mut self, steps: isize) {
+        if self.is_empty() { return; }
+
+        for _i in 0..steps.unsigned_abs() % self.len {
+            self.current = if steps > 0 { self.nodes[self.current].next } else { self.nodes[self.current].prev };
+        }
+    }
+
+    fn allocate(&mut self, node: Node<T>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // Insert right after the cursor and move the cursor onto the new item
+    pub fn push(&mut self, value: T) {
+        self.len += 1;
+        if self.len == 1 {
+            // Adding to an empty circle. Create a one-item circle
+            let index = self.allocate(Node { value: Some(value), prev: 0, next: 0 });
+            self.nodes[index].prev = index;
+            self.nodes[index].next = index;
+            self.current = index;
+            return;
+        }
+
+        let prev = self.current;
+        let next = self.nodes[prev].next;
+        let index = self.allocate(Node { value: Some(value), prev, next });
+        self.nodes[prev].next = index;
+        self.nodes[next].prev = index;
+        self.current = index;
+    }
+
+    // Remove the item at the cursor, moving the cursor to the one after it
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() { return None; }
+
+        let index = self.current;
+        let prev = self.nodes[index].prev;
+        let next = self.nodes[index].next;
+        self.nodes[prev].next = next;
+        self.nodes[next].prev = prev;
+
+        self.len -= 1;
+        self.current = next;
+        self.free.push(index);
+        self.nodes[index].value.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The items clockwise from the cursor, leaving the cursor where it was
+    fn items(circle: &mut Circle<u32>) -> Vec<u32> {
+        let mut items = Vec::new();
+        for _ in 0..circle.len {
+            items.push(*circle.nodes[circle.current].value.as_ref().unwrap());
+            circle.rotate(1);
+        }
+        items
+    }
+
+    #[test]
+    fn push_and_rotate() {
+        let mut circle = Circle::with_capacity(4);
+        assert!(circle.is_empty());
+        circle.push(0);
+        circle.push(1);
+        circle.push(2);
+        assert_eq!(items(&mut circle), vec![2, 0, 1]);
+
+        circle.rotate(-1);
+        assert_eq!(items(&mut circle), vec![1, 2, 0]);
+        circle.rotate(4);
+        assert_eq!(items(&mut circle), vec![2, 0, 1]);
+        circle.rotate(-5);
+        assert_eq!(items(&mut circle), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn pop_moves_to_next() {
+        let mut circle = Circle::with_capacity(3);
+        for i in 0..3 {
+            circle.push(i);
+        }
+        circle.rotate(1);
+        assert_eq!(circle.pop(), Some(0));
+        assert_eq!(items(&mut circle), vec![1, 2]);
+        assert_eq!(circle.pop(), Some(1));
+        assert_eq!(circle.pop(), Some(2));
+        assert!(circle.is_empty());
+        assert_eq!(circle.pop(), None);
+        circle.rotate(3);
+
+        circle.push(7);
+        assert_eq!(items(&mut circle), vec![7]);
+    }
+
+    #[test]
+    fn reuses_slots() {
+        let mut circle = Circle::with_capacity(2);
+        circle.push(0);
+        circle.push(1);
+        for i in 2..100 {
+            circle.pop();
+            circle.push(i);
+        }
+        assert_eq!(circle.nodes.len(), 2);
+        assert_eq!(items(&mut circle), vec![99, 0]);
+    }
+}
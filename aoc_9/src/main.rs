@@ -1,163 +1,123 @@
-struct Node {
-    value: usize,
-    prev: usize,
-    next: usize,
+#[macro_use] extern crate lazy_static;
+extern crate regex;
+
+mod circle;
+
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::fs::File;
+use std::env;
+use std::process;
+use std::cmp::Reverse;
+use regex::Regex;
+use circle::Circle;
+
+#[derive(Debug, Clone, Copy)]
+struct Game {
+    players: usize,
+    last_marble: usize,
 }
 
-impl Node {
-    fn new(value: usize, prev: usize, next: usize) -> Node {
-        Node { value, prev, next }
-    }
-}
-
-struct Circle {
-    nodes: Vec<Node>,
-    current: usize,
-    size: usize,
-}
-
-impl Circle {
-    fn new() -> Circle {
-        Circle { nodes: Vec::new(), current: std::usize::MAX, size: 0 }
-    }
-
-    fn insert(&mut self, value: usize) {
-        self.size += 1;
-        if self.size == 1 {
-            // Adding to an empty circle. Create a one-item circle
-            self.nodes.push(Node::new(value, 0, 0));
-            self.current = 0;
-            return;
-        }
-
-        let next = self.nodes[self.current].next;
-        self.nodes.push(Node::new(value, self.current, next));
-        self.nodes[self.current].next = self.nodes.len() - 1;
-        self.nodes[next].prev = self.nodes.len() - 1;
-    }
-
-    fn advance(&mut self, step: usize) {
-        for _i in 0..step {
-            self.current = self.nodes[self.current].next;
-        }
-    }
-
-    fn rewind(&mut self, step: usize) {
-        for i in 0..step {
-            //println!("{} curr: {} val {}", i, self.current, self.nodes[self.current].value);
-            self.current = self.nodes[self.current].prev;
+impl Game {
+    fn from_input(line: &str) -> Result<Game, String> {
+        lazy_static! {
+           static ref RE: Regex = Regex::new(r"^\s*(\d+) players; last marble is worth (\d+) points\s*$").unwrap();
         }
-    }
-
-    fn remove_current(&mut self) -> usize {
-        let val = self.nodes[self.current].value;
-        let prev = self.nodes[self.current].prev;
-        let next = self.nodes[self.current].next;
-        self.nodes[prev].next = next;
-        self.nodes[next].prev = prev;
-
-        // TODO remove node from 'nodes'
-        self.current = next;
-
-        val
-    }
 
-    fn print(&self) {
-        let mut c = self.current;
-        loop {
-            print!("{} ", self.nodes[c].value);
-            c = self.nodes[c].next;
-            if c == self.current { println!(""); break; }
+        let invalid = || format!("Unexpected game description: {:?}", line);
+        let captures = RE.captures(line).ok_or_else(invalid)?;
+        let game = Game {
+            players: captures[1].parse::<usize>().map_err(|_| invalid())?,
+            last_marble: captures[2].parse::<usize>().map_err(|_| invalid())?,
+        };
+        if game.players == 0 {
+            return Err(format!("A game needs at least one player: {:?}", line));
         }
+        Ok(game)
     }
 }
 
-fn optimized() {
-    let last_marble = 71032 * 100;
-    let special = 23;
+// Every `special`th marble isn't placed; instead it's scored along with the
+// marble `rewind` positions counter-clockwise of the current one
+#[derive(Debug, Clone, Copy)]
+struct Rules {
+    special: usize,
+    rewind: usize,
+}
 
-    let mut players = vec![0; 441];
-    let mut current_player = 0;
-    let mut circle = Circle::new();
-    circle.insert(0);
+fn read_input() -> Vec<Game> {
+    let input_file = File::open("src/input.txt").unwrap();
+    let reader = BufReader::new(input_file);
+    reader.lines()
+        .map(|line|line.unwrap())
+        .filter(|line|!line.trim().is_empty())
+        .map(|line|Game::from_input(&line).unwrap_or_else(|e| panic!("{}", e)))
+        .collect()
+}
 
-    for marble in 1..last_marble + 1 {
-        //circle.print();
-        if marble % 100 == 0  {
-            println!("Current: {}", marble);
-        }
-        current_player = (current_player + 1) % players.len();
-        if marble % special == 0 {
-            circle.rewind(7);
-            players[current_player] += marble + circle.remove_current();
+// Play the game and return every player's score, the first player's at index 0
+fn play(game: Game, rules: Rules) -> Vec<usize> {
+    let mut scores = vec![0; game.players];
+    let mut circle = Circle::with_capacity(game.last_marble + 1);
+    circle.push(0);
+
+    for marble in 1..game.last_marble + 1 {
+        let current_player = (marble - 1) % game.players;
+        if marble % rules.special == 0 {
+            // With `special` as low as 1 the circle can run out of marbles,
+            // leaving only the special one to score
+            circle.rotate(-(rules.rewind as isize));
+            scores[current_player] += marble + circle.pop().unwrap_or(0);
             continue;
         }
 
-        circle.advance(1);
-        circle.insert(marble);
-        circle.advance(1);
+        circle.rotate(1);
+        circle.push(marble);
     }
 
-    println!("{:?}", players);
-    println!("{:?}", players.iter().max().unwrap());
+    scores
 }
 
-
-#[derive(Debug)]
-struct MarbleCircle {
-    marbles: Vec<usize>,
-    current: usize,
+// The player with the highest score, numbered from 1, and the score. Ties go
+// to the lower numbered player.
+fn winner(scores: &[usize]) -> Option<(usize, usize)> {
+    scores.iter()
+        .enumerate()
+        .max_by_key(|&(i, &score)| (score, Reverse(i)))
+        .map(|(i, &score)| (i + 1, score))
 }
 
-impl MarbleCircle {
-    fn new() -> MarbleCircle {
-        MarbleCircle { marbles: vec![0], current: 0}
+fn report(game: Game, rules: Rules, show_scores: bool) {
+    let scores = play(game, rules);
+    match winner(&scores) {
+        Some((player, score)) =>
+            println!("{} players, last marble {}: player {} wins with {}", game.players, game.last_marble, player, score),
+        None => println!("{} players, last marble {}: nobody plays", game.players, game.last_marble),
     }
 
-    fn insert(&mut self, marble: usize) {
-        let insert_position = (self.current + 1) % self.marbles.len() + 1;
-        self.marbles.insert(insert_position, marble);
-        self.current = insert_position;
-    }
-
-    fn remove(&mut self) -> usize {
-        let removal_position =
-            if self.current >= 7 {
-                self.current - 7
-            } else {
-                self.current + self.marbles.len() - 7
-            };
-
-        let result = self.marbles.remove(removal_position);
-        self.current = removal_position % self.marbles.len();
-
-        result
+    if show_scores {
+        for (i, score) in scores.iter().enumerate() {
+            println!("{:>6} {:>12}", i + 1, score);
+        }
     }
 }
 
+// Usage: aoc_9 [--special N] [--rewind N] [--scores]
 fn main() {
-    let last_marble = 71032;
-    let special = 23;
-
-    let mut players = vec![0; 441];
-    let mut circle = MarbleCircle::new();
-    let mut current_player = 0;
-
-    for marble in 1..last_marble + 1 {
-        if marble % 100 == 0  {
-            println!("Current: {}", marble);
-        }
-        current_player = (current_player + 1) % players.len();
-        if marble % special == 0 {
-            players[current_player] += marble + circle.remove();
-            continue;
-        }
-
-        circle.insert(marble);
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let option = |name: &str, default: usize| match args.iter().position(|x| x == name) {
+        None => default,
+        Some(i) => args.get(i + 1).and_then(|x| x.parse::<usize>().ok()).unwrap_or_else(|| {
+            eprintln!("Usage: aoc_9 [--special N] [--rewind N] [--scores]");
+            process::exit(1);
+        }),
+    };
+    let rules = Rules { special: option("--special", 23), rewind: option("--rewind", 7) };
+    assert!(rules.special > 0, "--special must be positive");
+    let show_scores = args.iter().any(|x| x == "--scores");
+
+    for game in read_input() {
+        report(game, rules, show_scores);
+        report(Game { last_marble: game.last_marble * 100, ..game }, rules, show_scores);
     }
-
-    println!("{:?}", players);
-    println!("{:?}", players.iter().max().unwrap());
-
-    optimized();
 }